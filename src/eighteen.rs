@@ -1,3 +1,200 @@
+use crate::grid::{Grid, Pos};
+use crate::pathfinding::dijkstra;
+
+// The direct routes from a robot start or key to each key: how far it is, and
+// which keys are needed for the doors along the way.
+#[derive(Clone, Copy, Debug)]
+struct Edge {
+    key: usize,
+    distance: usize,
+    required: u32,
+}
+
+#[derive(Clone, Eq, Ord, PartialEq, PartialOrd)]
+struct State {
+    robots: Vec<usize>,
+    keys: u32,
+}
+
 pub fn start(input: &str) {
-    println!("{}", input);
+    let grid = Grid::parse(input);
+    if grid.find(b'@').is_none() {
+        return println!("The map has no entrance");
+    }
+    match collect_keys(&grid) {
+        Some(steps) => println!("Fewest steps: {}", steps),
+        None => println!("The keys can't all be collected"),
+    }
+
+    match collect_keys(&split_vault(&grid)) {
+        Some(steps) => println!("Fewest steps with four robots: {}", steps),
+        None => println!("The keys can't all be collected with four robots"),
+    }
+}
+
+fn key_bit(c: u8) -> u32 {
+    1 << (c.to_ascii_lowercase() - b'a')
+}
+
+// Replaces the single entrance with four walled-off robots, as in part two.
+// Maps that already have several entrances are left as they are.
+fn split_vault(grid: &Grid) -> Grid {
+    let mut grid = grid.clone();
+    let entrances: Vec<Pos> = grid.positions(|c| c == b'@').collect();
+    if let [(x, y)] = entrances[..] {
+        if x == 0 || y == 0 || x + 1 >= grid.width() || y + 1 >= grid.height() {
+            return grid;
+        }
+        for (dy, row) in [b"@#@", b"###", b"@#@"].iter().enumerate() {
+            for (dx, c) in row.iter().enumerate() {
+                grid.set((x + dx - 1, y + dy - 1), *c);
+            }
+        }
+    }
+    grid
+}
+
+// Nodes are the robot starts followed by the keys, in reading order.
+fn edges(grid: &Grid, nodes: &[Pos]) -> Vec<Vec<Edge>> {
+    nodes
+        .iter()
+        .map(|from| {
+            let paths = grid.bfs(*from, |c| c != b'#');
+            nodes
+                .iter()
+                .enumerate()
+                .filter(|(_, to)| *to != from && grid.get(**to).is_some_and(is_key))
+                .filter_map(|(key, to)| {
+                    let route = paths.route(*to);
+                    let required = route[..route.len().checked_sub(1)?]
+                        .iter()
+                        .filter_map(|pos| grid.get(*pos))
+                        .filter(u8::is_ascii_uppercase)
+                        .fold(0, |mask, door| mask | key_bit(door));
+                    Some(Edge {
+                        key,
+                        distance: route.len(),
+                        required,
+                    })
+                })
+                .collect()
+        })
+        .collect()
+}
+
+fn is_key(c: u8) -> bool {
+    c.is_ascii_lowercase()
+}
+
+// Fewest steps for the robots to collect every key, or None if it can't be
+// done or there are no robots.
+fn collect_keys(grid: &Grid) -> Option<usize> {
+    let robots: Vec<Pos> = grid.positions(|c| c == b'@').collect();
+    if robots.is_empty() {
+        return None;
+    }
+    let nodes: Vec<Pos> = robots
+        .iter()
+        .cloned()
+        .chain(grid.positions(is_key))
+        .collect();
+    let edges = edges(grid, &nodes);
+    let all_keys = nodes
+        .iter()
+        .filter_map(|pos| grid.get(*pos))
+        .filter(|c| is_key(*c))
+        .fold(0, |mask, key| mask | key_bit(key));
+
+    let start = State {
+        robots: (0..robots.len()).collect(),
+        keys: 0,
+    };
+    let neighbours = |state: &State| {
+        let mut next = Vec::new();
+        for (robot, node) in state.robots.iter().enumerate() {
+            for edge in edges[*node].iter() {
+                let bit = key_bit(grid.get(nodes[edge.key]).unwrap());
+                if state.keys & bit != 0 || edge.required & !state.keys != 0 {
+                    continue;
+                }
+                let mut robots = state.robots.clone();
+                robots[robot] = edge.key;
+                let keys = state.keys | bit;
+                next.push((State { robots, keys }, edge.distance));
+            }
+        }
+        next
+    };
+
+    dijkstra(start, neighbours, |state| state.keys == all_keys).map(|(_, steps)| steps)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_one() {
+        let examples = [
+            ("#########\n#b.A.@.a#\n#########", 8),
+            (
+                "########################\n\
+                 #f.D.E.e.C.b.A.@.a.B.c.#\n\
+                 ######################.#\n\
+                 #d.....................#\n\
+                 ########################",
+                86,
+            ),
+            (
+                "########################\n\
+                 #...............b.C.D.f#\n\
+                 #.######################\n\
+                 #.....@.a.B.c.d.A.e.F.g#\n\
+                 ########################",
+                132,
+            ),
+            (
+                "########################\n\
+                 #@..............ac.GI.b#\n\
+                 ###d#e#f################\n\
+                 ###A#B#C################\n\
+                 ###g#h#i################\n\
+                 ########################",
+                81,
+            ),
+        ];
+        for (input, steps) in examples.iter() {
+            assert_eq!(collect_keys(&Grid::parse(input)), Some(*steps));
+        }
+    }
+
+    #[test]
+    fn test_two() {
+        let grid = Grid::parse(
+            "#######\n\
+             #a.#Cd#\n\
+             ##...##\n\
+             ##.@.##\n\
+             ##...##\n\
+             #cB#Ab#\n\
+             #######",
+        );
+        let split = split_vault(&grid);
+        assert_eq!(split.positions(|c| c == b'@').count(), 4);
+        assert_eq!(collect_keys(&split), Some(8));
+
+        let grid = Grid::parse(
+            "#############\n\
+             #DcBa.#.GhKl#\n\
+             #.###@#@#I###\n\
+             #e#d#####j#k#\n\
+             ###C#@#@###J#\n\
+             #fEbA.#.FgHi#\n\
+             #############",
+        );
+        assert_eq!(collect_keys(&split_vault(&grid)), Some(32));
+
+        assert_eq!(collect_keys(&Grid::parse("")), None);
+        assert_eq!(collect_keys(&split_vault(&Grid::parse("#.a#"))), None);
+    }
 }
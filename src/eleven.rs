@@ -108,6 +108,13 @@ fn run_robot(program: &mut IntCodeProgram, robot: &mut Robot, hull: &mut HashMap
         let hull_point = hull.entry(robot.loc).or_insert(0);
         program.in_buf.push(*hull_point as i64);
         program.run();
+        match program.status {
+            IntCodeStatus::AwaitingInput | IntCodeStatus::Halted => {}
+            ref status => {
+                println!("The robot stopped: {:?}", status);
+                break;
+            }
+        }
 
         let color = program.out_buf.remove(0) as u8;
         if color != *hull_point {
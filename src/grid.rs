@@ -0,0 +1,220 @@
+//! Helpers for the puzzles that work on rectangular ASCII maps.
+
+use alloc::collections::VecDeque;
+use alloc::string::String;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt;
+
+/// A cell position as `(x, y)`, with `y` growing downwards.
+pub type Pos = (usize, usize);
+
+/// One of the four grid directions.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Direction {
+    /// Towards row 0.
+    Up,
+    /// Towards higher columns.
+    Right,
+    /// Towards higher rows.
+    Down,
+    /// Towards column 0.
+    Left,
+}
+
+impl Direction {
+    /// All directions, clockwise from `Up`.
+    pub const ALL: [Direction; 4] = [
+        Direction::Up,
+        Direction::Right,
+        Direction::Down,
+        Direction::Left,
+    ];
+
+    /// The `(dx, dy)` offset of a single step.
+    pub fn delta(self) -> (i64, i64) {
+        match self {
+            Direction::Up => (0, -1),
+            Direction::Right => (1, 0),
+            Direction::Down => (0, 1),
+            Direction::Left => (-1, 0),
+        }
+    }
+
+    /// The direction a quarter turn anticlockwise.
+    pub fn turn_left(self) -> Direction {
+        Direction::ALL[(self as usize + 3) % 4]
+    }
+
+    /// The direction a quarter turn clockwise.
+    pub fn turn_right(self) -> Direction {
+        Direction::ALL[(self as usize + 1) % 4]
+    }
+
+    /// The opposite direction.
+    pub fn reverse(self) -> Direction {
+        Direction::ALL[(self as usize + 2) % 4]
+    }
+}
+
+/// A rectangular grid of ASCII cells.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Grid {
+    width: usize,
+    height: usize,
+    cells: Vec<u8>,
+}
+
+impl Grid {
+    /// Creates a grid with every cell set to `fill`.
+    pub fn new(width: usize, height: usize, fill: u8) -> Grid {
+        Grid {
+            width,
+            height,
+            cells: vec![fill; width * height],
+        }
+    }
+
+    /// Parses one row per line. Leading whitespace is kept, trailing blank
+    /// lines are dropped and short rows are padded with spaces.
+    pub fn parse(input: &str) -> Grid {
+        let mut rows: Vec<&str> = input.lines().collect();
+        while rows.last().is_some_and(|row| row.trim().is_empty()) {
+            rows.pop();
+        }
+
+        let width = rows.iter().map(|row| row.len()).max().unwrap_or(0);
+        let mut grid = Grid::new(width, rows.len(), b' ');
+        for (y, row) in rows.iter().enumerate() {
+            for (x, c) in row.bytes().enumerate() {
+                grid.cells[y * width + x] = c;
+            }
+        }
+        grid
+    }
+
+    /// The number of columns.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// The number of rows.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// The cell at `pos`, or `None` when it is out of bounds.
+    pub fn get(&self, pos: Pos) -> Option<u8> {
+        if pos.0 < self.width && pos.1 < self.height {
+            return Some(self.cells[pos.1 * self.width + pos.0]);
+        }
+        None
+    }
+
+    /// Sets the cell at `pos`, which must be in bounds.
+    pub fn set(&mut self, pos: Pos, value: u8) {
+        assert!(pos.0 < self.width && pos.1 < self.height);
+        self.cells[pos.1 * self.width + pos.0] = value;
+    }
+
+    /// The first position, in reading order, holding `value`.
+    pub fn find(&self, value: u8) -> Option<Pos> {
+        self.positions(|c| c == value).next()
+    }
+
+    /// Every position, in reading order, whose cell matches `pred`.
+    pub fn positions<'a, F>(&'a self, pred: F) -> impl Iterator<Item = Pos> + 'a
+    where
+        F: Fn(u8) -> bool + 'a,
+    {
+        let width = self.width;
+        self.cells
+            .iter()
+            .enumerate()
+            .filter(move |(_, c)| pred(**c))
+            .map(move |(i, _)| (i % width, i / width))
+    }
+
+    /// The neighbouring position in `dir`, if it is in bounds.
+    pub fn step(&self, pos: Pos, dir: Direction) -> Option<Pos> {
+        let (dx, dy) = dir.delta();
+        let x = pos.0 as i64 + dx;
+        let y = pos.1 as i64 + dy;
+        if x < 0 || y < 0 || x >= self.width as i64 || y >= self.height as i64 {
+            return None;
+        }
+        Some((x as usize, y as usize))
+    }
+
+    /// The in-bounds orthogonal neighbours of `pos`.
+    pub fn neighbours(&self, pos: Pos) -> impl Iterator<Item = Pos> + '_ {
+        Direction::ALL
+            .iter()
+            .filter_map(move |dir| self.step(pos, *dir))
+    }
+
+    /// Breadth-first search from `start` over the cells accepted by
+    /// `passable`. The start cell itself is always entered.
+    pub fn bfs<F: Fn(u8) -> bool>(&self, start: Pos, passable: F) -> Paths {
+        let index = |pos: Pos| pos.1 * self.width + pos.0;
+        let mut paths = Paths {
+            width: self.width,
+            distance: vec![None; self.cells.len()],
+            previous: vec![None; self.cells.len()],
+        };
+        paths.distance[index(start)] = Some(0);
+
+        let mut queue = VecDeque::new();
+        queue.push_back((start, 0));
+        while let Some((pos, dist)) = queue.pop_front() {
+            for next in self.neighbours(pos) {
+                if paths.distance[index(next)].is_none() && passable(self.get(next).unwrap()) {
+                    paths.distance[index(next)] = Some(dist + 1);
+                    paths.previous[index(next)] = Some(pos);
+                    queue.push_back((next, dist + 1));
+                }
+            }
+        }
+        paths
+    }
+}
+
+/// Shortest paths from one cell, as found by [`Grid::bfs`].
+#[derive(Clone, Debug)]
+pub struct Paths {
+    width: usize,
+    distance: Vec<Option<usize>>,
+    previous: Vec<Option<Pos>>,
+}
+
+impl Paths {
+    /// The number of steps to `pos`, or `None` if it can't be reached.
+    pub fn distance(&self, pos: Pos) -> Option<usize> {
+        *self.distance.get(pos.1 * self.width + pos.0)?
+    }
+
+    /// The cells on a shortest path to `pos`, excluding the start. Empty if
+    /// `pos` can't be reached.
+    pub fn route(&self, pos: Pos) -> Vec<Pos> {
+        let mut route = Vec::new();
+        if self.distance(pos).is_none() {
+            return route;
+        }
+        let mut current = pos;
+        while let Some(previous) = self.previous[current.1 * self.width + current.0] {
+            route.push(current);
+            current = previous;
+        }
+        route.reverse();
+        route
+    }
+}
+
+impl fmt::Display for Grid {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for row in self.cells.chunks(self.width.max(1)) {
+            writeln!(f, "{}", String::from_utf8_lossy(row))?;
+        }
+        Ok(())
+    }
+}
@@ -1,172 +1,604 @@
-use std::usize;
+//! The Intcode virtual machine used by many of this year's puzzles.
+//!
+//! [`IntCodeProgram`] runs a program until it halts, needs input or hits an
+//! error; the submodules add tooling around it such as a loader, a
+//! disassembler, memory-mapped devices and a small compiler.
+//!
+//! Memory is `i64` by default but can be any [`Word`], such as `i128` for
+//! programs that overflow 64 bits. The loader, disassembler and devices work
+//! in `i64`.
+//!
+//! The machine itself only needs `alloc`. Loading programs from text or
+//! files, devices, threaded search and the async executor need `std`.
 
+use alloc::collections::BTreeSet;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+#[cfg(feature = "std")]
+use core::ops::Range;
+#[cfg(feature = "std")]
+use std::sync::Mutex;
+
+#[cfg(feature = "std")]
+pub mod compiler;
+#[cfg(feature = "std")]
+pub mod devices;
+#[cfg(feature = "std")]
+pub mod executor;
+#[cfg(feature = "std")]
+mod gzip;
+#[cfg(feature = "std")]
+pub mod loader;
+pub mod memory;
+pub mod opcodes;
+#[cfg(feature = "std")]
+pub mod search;
+pub mod session;
+pub mod symbolic;
+pub mod word;
+
+#[cfg(feature = "std")]
+use devices::{Device, Mapping};
+#[cfg(feature = "std")]
+use loader::LoadError;
+use memory::Snapshot;
+use opcodes::{OpcodeTable, Step, MAX_PARAMS};
+use session::{Event, Session};
+use word::Word;
+
+/// A running Intcode machine: its memory, registers and I/O buffers.
+///
+/// With the `serde` feature the machine can be saved mid-run. The opcode
+/// table and mapped devices are skipped: a deserialized machine runs the
+/// standard opcodes with no devices until `set_opcodes` and `map_device` are
+/// called again.
+///
+/// Cloning a machine also copies its mapped devices, so a clone's device
+/// reads and writes don't affect the original.
 #[derive(Clone, Debug)]
-pub struct IntCodeProgram {
-    program: Vec<i64>,
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct IntCodeProgram<W: Word = i64> {
+    program: Vec<W>,
     pointer: usize,
-    rel_base: usize,
-    pub in_buf: Vec<i64>,
-    pub out_buf: Vec<i64>,
+    rel_base: W,
+    steps: u64,
+    budget: Option<u64>,
+    breakpoints: BTreeSet<usize>,
+    // Handlers can't be serialized, so this comes back as the standard table.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    opcodes: Arc<OpcodeTable<W>>,
+    // Devices are trait objects, so this comes back empty.
+    #[cfg(feature = "std")]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    devices: Vec<Mapping>,
+    session: Option<Session<W>>,
+    /// Inputs waiting to be read, oldest first.
+    pub in_buf: Vec<W>,
+    /// Outputs written so far, oldest first.
+    pub out_buf: Vec<W>,
+    /// Why the machine last stopped.
     pub status: IntCodeStatus,
 }
 
+/// Why the machine last stopped, or `Ready` if it can keep going.
 #[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum IntCodeStatus {
-    Paused,
-    Halted,
+    /// Not yet run, or able to continue.
     Ready,
+    /// Stopped on an input instruction with an empty `in_buf`.
+    AwaitingInput,
+    /// Reached opcode 99.
+    Halted,
+    /// Stopped on a fault.
+    Error(IntCodeError),
+    /// Used up the step budget set with `set_budget`.
+    BudgetExhausted,
+    /// Stopped before the instruction at a breakpoint.
+    Breakpoint(usize),
+}
+
+/// A fault that stops execution, with the instruction pointer it occurred at.
+/// Values too wide for `i64` are saturated.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum IntCodeError {
+    /// The instruction's opcode isn't in the opcode table.
+    UnknownOpcode {
+        /// The opcode, without its parameter modes.
+        opcode: i64,
+        /// The faulting instruction's address.
+        pointer: usize,
+    },
+    /// A parameter mode other than 0, 1 or 2.
+    UnknownMode {
+        /// The mode digit.
+        mode: i64,
+        /// The faulting instruction's address.
+        pointer: usize,
+    },
+    /// A read or write outside memory, or a negative address.
+    InvalidAddress {
+        /// The address that was out of range.
+        address: i64,
+        /// The faulting instruction's address.
+        pointer: usize,
+    },
 }
 
 impl IntCodeProgram {
+    /// Parses a comma separated program, panicking if it is malformed.
+    #[cfg(feature = "std")]
     pub fn from_input(input: &str) -> IntCodeProgram {
-        let mut program: Vec<i64> = input
-            .trim()
-            .split(',')
-            .map(|num| {
-                return num.parse::<i64>().unwrap();
-            })
-            .collect();
-        program.resize(program.len() * 128, 0);
+        match loader::parse(input) {
+            Ok(program) => IntCodeProgram::from_memory(program),
+            Err(e) => panic!("Could not parse Intcode program: {}", e),
+        }
+    }
+
+    /// Loads a program from a text, gzip or binary file.
+    #[cfg(feature = "std")]
+    pub fn load(filename: &str) -> Result<IntCodeProgram, LoadError> {
+        Ok(IntCodeProgram::from_memory(loader::load(filename)?))
+    }
+
+    /// Saves memory in the binary format understood by `load`.
+    #[cfg(feature = "std")]
+    pub fn save(&self, filename: &str) -> Result<(), LoadError> {
+        loader::save(filename, &self.program)
+    }
+
+    /// Creates a machine from raw memory, padded with zeroes for scratch space.
+    pub fn from_memory(program: Vec<i64>) -> IntCodeProgram {
+        IntCodeProgram::from_words(program)
+    }
+}
+
+impl<W: Word> IntCodeProgram<W> {
+    /// Like `from_memory`, for any word type.
+    pub fn from_words(mut program: Vec<W>) -> IntCodeProgram<W> {
+        program.resize(program.len() * 128, W::from_i64(0));
         IntCodeProgram {
-            program: program,
+            program,
             pointer: 0,
-            rel_base: 0,
+            rel_base: W::from_i64(0),
+            steps: 0,
+            budget: None,
+            breakpoints: BTreeSet::new(),
+            opcodes: Arc::new(OpcodeTable::standard()),
+            #[cfg(feature = "std")]
+            devices: Vec::new(),
+            session: None,
             status: IntCodeStatus::Ready,
             in_buf: Vec::new(),
             out_buf: Vec::new(),
         }
     }
 
-    pub fn set_at(&mut self, idx: usize, value: i64) {
-        self.program[idx] = value;
+    /// The address of the next instruction.
+    pub fn pointer(&self) -> usize {
+        self.pointer
+    }
+
+    /// The relative base used by mode 2 parameters.
+    pub fn rel_base(&self) -> W {
+        self.rel_base.clone()
+    }
+
+    /// The number of cells of memory.
+    pub fn memory_len(&self) -> usize {
+        self.program.len()
+    }
+
+    /// The number of instructions executed so far.
+    pub fn steps(&self) -> u64 {
+        self.steps
+    }
+
+    /// Limits `run` to a total number of steps; `None` removes the limit.
+    pub fn set_budget(&mut self, budget: Option<u64>) {
+        self.budget = budget;
+    }
+
+    /// Makes `run` stop before executing the instruction at `pointer`.
+    pub fn add_breakpoint(&mut self, pointer: usize) {
+        self.breakpoints.insert(pointer);
+    }
+
+    /// Clears a breakpoint set with `add_breakpoint`.
+    pub fn remove_breakpoint(&mut self, pointer: usize) {
+        self.breakpoints.remove(&pointer);
+    }
+
+    /// Replaces the instruction set, e.g. to add custom opcodes.
+    pub fn set_opcodes(&mut self, opcodes: OpcodeTable<W>) {
+        self.opcodes = Arc::new(opcodes);
+    }
+
+    /// The instruction set in use.
+    pub fn opcodes(&self) -> &OpcodeTable<W> {
+        &self.opcodes
+    }
+
+    /// Captures the full machine state for `restore` or `memory::diff`.
+    pub fn snapshot(&self) -> Snapshot<W> {
+        Snapshot {
+            memory: self.program.clone(),
+            pointer: self.pointer,
+            rel_base: self.rel_base.clone(),
+            steps: self.steps,
+            in_buf: self.in_buf.clone(),
+            out_buf: self.out_buf.clone(),
+            status: self.status.clone(),
+        }
+    }
+
+    /// Puts the machine back into the state captured by `snapshot`.
+    pub fn restore(&mut self, snapshot: &Snapshot<W>) {
+        self.program = snapshot.memory.clone();
+        self.pointer = snapshot.pointer;
+        self.rel_base = snapshot.rel_base.clone();
+        self.steps = snapshot.steps;
+        self.in_buf = snapshot.in_buf.clone();
+        self.out_buf = snapshot.out_buf.clone();
+        self.status = snapshot.status.clone();
+    }
+
+    /// Maps `device` over the address range, returning a handle to it.
+    /// Instructions and their parameters are fetched through the device too.
+    ///
+    /// Clones of this machine, including the ones `search::Search` runs
+    /// candidates on, get their own copy of the device, which the handle
+    /// doesn't see.
+    #[cfg(feature = "std")]
+    pub fn map_device<D: Device + 'static>(
+        &mut self,
+        range: Range<usize>,
+        device: D,
+    ) -> Arc<Mutex<D>> {
+        let device = Arc::new(Mutex::new(device));
+        self.devices.push(Mapping {
+            range,
+            device: device.clone(),
+        });
+        device
+    }
+
+    /// Starts recording I/O into a fresh session.
+    pub fn record(&mut self) {
+        self.session = Some(Session::default());
+    }
+
+    /// Stops recording and returns the session, if one was started.
+    pub fn take_session(&mut self) -> Option<Session<W>> {
+        self.session.take()
+    }
+
+    fn log(&mut self, event: Event<W>) {
+        if let Some(session) = self.session.as_mut() {
+            session.events.push(event);
+        }
+    }
+
+    /// Writes `value` at `idx`, or to the device mapped there. Panics if
+    /// `idx` is past the end of memory.
+    pub fn set_at(&mut self, idx: usize, value: W) {
+        self.write(idx, value);
+    }
+
+    /// Reads the value at `idx`, or from the device mapped there. Panics if
+    /// `idx` is past the end of memory.
+    pub fn get(&self, idx: usize) -> W {
+        self.read(idx)
     }
 
-    pub fn get(&self, idx: usize) -> i64 {
-        return self.program[idx];
+    fn read(&self, idx: usize) -> W {
+        #[cfg(feature = "std")]
+        for mapping in self.devices.iter() {
+            if mapping.range.contains(&idx) {
+                let value = mapping
+                    .device
+                    .lock()
+                    .unwrap()
+                    .read(idx - mapping.range.start);
+                return W::from_i64(value);
+            }
+        }
+        self.program[idx].clone()
+    }
+
+    fn write(&mut self, idx: usize, value: W) {
+        #[cfg(feature = "std")]
+        for mapping in self.devices.iter() {
+            if mapping.range.contains(&idx) {
+                let offset = idx - mapping.range.start;
+                mapping.device.lock().unwrap().write(offset, value.to_i64());
+                return;
+            }
+        }
+        self.program[idx] = value;
     }
 
+    /// Runs until the status is no longer `Ready`. Resuming from a
+    /// breakpoint steps over it.
     pub fn run(&mut self) {
+        let mut resuming = match self.status {
+            IntCodeStatus::Breakpoint(pointer) => pointer == self.pointer,
+            _ => false,
+        };
         self.status = IntCodeStatus::Ready;
 
         while self.status == IntCodeStatus::Ready {
-            let (opcode, p_one, p_two, p_three) = self.get_opcode_and_parameters();
-            match opcode {
-                1 => self.opcode_one(p_one, p_two, p_three),
-                2 => self.opcode_two(p_one, p_two, p_three),
-                3 => self.opcode_three(p_one),
-                4 => self.opcode_four(p_one),
-                5 => self.opcode_five(p_one, p_two),
-                6 => self.opcode_six(p_one, p_two),
-                7 => self.opcode_seven(p_one, p_two, p_three),
-                8 => self.opcode_eight(p_one, p_two, p_three),
-                9 => self.opcode_nine(p_one),
-                99 => self.opcode_ninety_nine(),
-                _ => panic!("Unknown opcode: {}", opcode),
+            if self.budget.is_some_and(|budget| self.steps >= budget) {
+                self.status = IntCodeStatus::BudgetExhausted;
+            } else if self.breakpoints.contains(&self.pointer) && !resuming {
+                self.status = IntCodeStatus::Breakpoint(self.pointer);
+            } else {
+                self.step();
             }
+            resuming = false;
         }
     }
 
-    fn opcode_one(&mut self, one: usize, two: usize, three: usize) {
-        self.program[three] = self.program[one] + self.program[two];
-        self.pointer += 4;
+    /// Executes a single instruction.
+    pub fn step(&mut self) {
+        self.status = IntCodeStatus::Ready;
+        if let Err(err) = self.execute() {
+            self.status = IntCodeStatus::Error(err);
+        }
     }
 
-    fn opcode_two(&mut self, one: usize, two: usize, three: usize) {
-        self.program[three] = self.program[one] * self.program[two];
-        self.pointer += 4;
+    fn execute(&mut self) -> Result<(), IntCodeError> {
+        let instr = match self.fetch(self.pointer) {
+            Some(instr) => instr,
+            None => {
+                return Err(IntCodeError::InvalidAddress {
+                    address: self.pointer as i64,
+                    pointer: self.pointer,
+                })
+            }
+        };
+        let (mut modes, code) = instr.div_rem(100);
+        let opcode = match self.opcodes.get(code) {
+            Some(opcode) => *opcode,
+            None => {
+                return Err(IntCodeError::UnknownOpcode {
+                    opcode: code,
+                    pointer: self.pointer,
+                })
+            }
+        };
+
+        let mut params = [0; MAX_PARAMS];
+        for (i, param) in params.iter_mut().take(opcode.params).enumerate() {
+            let (rest, mode) = modes.div_rem(10);
+            *param = self.get_index_from_mode(mode, self.pointer + i + 1)?;
+            modes = rest;
+        }
+
+        match (opcode.handler)(self, &params[..opcode.params]) {
+            Step::Next => self.pointer += opcode.params + 1,
+            Step::Jump(pointer) => self.pointer = pointer,
+            Step::Wait => {
+                self.status = IntCodeStatus::AwaitingInput;
+                return Ok(());
+            }
+            Step::Halt => self.status = IntCodeStatus::Halted,
+        }
+        self.steps += 1;
+        Ok(())
     }
 
-    fn opcode_three(&mut self, one: usize) {
-        if self.in_buf.get(0) == None {
-            self.status = IntCodeStatus::Paused;
-        } else {
-            self.program[one] = self.in_buf.remove(0);
-            self.pointer += 2;
+    fn opcode_one(&mut self, one: usize, two: usize, three: usize) -> Step {
+        self.write(three, self.read(one).add(&self.read(two)));
+        Step::Next
+    }
+
+    fn opcode_two(&mut self, one: usize, two: usize, three: usize) -> Step {
+        self.write(three, self.read(one).mul(&self.read(two)));
+        Step::Next
+    }
+
+    fn opcode_three(&mut self, one: usize) -> Step {
+        if self.in_buf.is_empty() {
+            return Step::Wait;
         }
+        let value = self.in_buf.remove(0);
+        self.log(Event::Input(value.clone()));
+        self.write(one, value);
+        Step::Next
     }
 
-    fn opcode_four(&mut self, one: usize) {
-        self.out_buf.push(self.program[one]);
-        self.pointer += 2;
+    fn opcode_four(&mut self, one: usize) -> Step {
+        let value = self.read(one);
+        self.log(Event::Output(value.clone()));
+        self.out_buf.push(value);
+        Step::Next
     }
 
-    fn opcode_five(&mut self, one: usize, two: usize) {
-        if self.program[one] != 0 {
-            self.pointer = self.program[two] as usize;
-        } else {
-            self.pointer += 3;
+    fn opcode_five(&mut self, one: usize, two: usize) -> Step {
+        if !self.read(one).is_zero() {
+            return Step::Jump(self.read(two).to_i64() as usize);
         }
+        Step::Next
     }
 
-    fn opcode_six(&mut self, one: usize, two: usize) {
-        if self.program[one] == 0 {
-            self.pointer = self.program[two] as usize;
-        } else {
-            self.pointer += 3;
+    fn opcode_six(&mut self, one: usize, two: usize) -> Step {
+        if self.read(one).is_zero() {
+            return Step::Jump(self.read(two).to_i64() as usize);
         }
+        Step::Next
     }
 
-    fn opcode_seven(&mut self, one: usize, two: usize, three: usize) {
-        if self.program[one] < self.program[two] {
-            self.program[three] = 1;
-        } else {
-            self.program[three] = 0;
+    fn opcode_seven(&mut self, one: usize, two: usize, three: usize) -> Step {
+        let flag = self.read(one) < self.read(two);
+        self.write(three, W::from_i64(flag as i64));
+        Step::Next
+    }
+
+    fn opcode_eight(&mut self, one: usize, two: usize, three: usize) -> Step {
+        let flag = self.read(one) == self.read(two);
+        self.write(three, W::from_i64(flag as i64));
+        Step::Next
+    }
+
+    fn opcode_nine(&mut self, one: usize) -> Step {
+        self.rel_base = self.rel_base.add(&self.read(one));
+        Step::Next
+    }
+
+    fn opcode_ninety_nine(&mut self) -> Step {
+        Step::Halt
+    }
+
+    fn get_index_from_mode(&self, mode: i64, idx: usize) -> Result<usize, IntCodeError> {
+        let param = || {
+            self.fetch(idx).ok_or(IntCodeError::InvalidAddress {
+                address: idx as i64,
+                pointer: self.pointer,
+            })
+        };
+        let address = match mode {
+            0 => param()?.to_i64(),
+            1 => idx as i64,
+            2 => self.rel_base.add(&param()?).to_i64(),
+            _ => {
+                return Err(IntCodeError::UnknownMode {
+                    mode,
+                    pointer: self.pointer,
+                })
+            }
+        };
+
+        if address < 0 || (address as usize >= self.program.len() && !self.mapped(address)) {
+            return Err(IntCodeError::InvalidAddress {
+                address,
+                pointer: self.pointer,
+            });
         }
-        self.pointer += 4;
+        Ok(address as usize)
     }
 
-    fn opcode_eight(&mut self, one: usize, two: usize, three: usize) {
-        if self.program[one] == self.program[two] {
-            self.program[three] = 1;
+    // Reads memory or a mapped device, or None if there's nothing at `idx`.
+    fn fetch(&self, idx: usize) -> Option<W> {
+        if idx < self.program.len() || self.mapped(idx as i64) {
+            Some(self.read(idx))
         } else {
-            self.program[three] = 0;
+            None
         }
-        self.pointer += 4;
     }
 
-    fn opcode_nine(&mut self, one: usize) {
-        self.rel_base = (self.rel_base as i64 + self.program[one]) as usize;
-        self.pointer += 2;
+    #[cfg(feature = "std")]
+    fn mapped(&self, address: i64) -> bool {
+        self.devices
+            .iter()
+            .any(|mapping| mapping.range.contains(&(address as usize)))
+    }
+
+    #[cfg(not(feature = "std"))]
+    fn mapped(&self, _address: i64) -> bool {
+        false
     }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
 
-    fn opcode_ninety_nine(&mut self) {
-        self.status = IntCodeStatus::Halted;
+    #[test]
+    fn test_status() {
+        let mut program = IntCodeProgram::from_memory(vec![3, 9, 4, 9, 1105, 1, 0]);
+        program.add_breakpoint(2);
+        program.run();
+        assert_eq!(program.status, IntCodeStatus::AwaitingInput);
+
+        program.in_buf.push(7);
+        program.run();
+        assert_eq!(program.status, IntCodeStatus::Breakpoint(2));
+        assert_eq!((program.pointer(), program.steps()), (2, 1));
+
+        program.set_budget(Some(3));
+        program.run();
+        assert_eq!(program.status, IntCodeStatus::BudgetExhausted);
+        assert_eq!(program.out_buf, vec![7]);
+        assert_eq!((program.pointer(), program.steps()), (0, 3));
     }
 
-    fn get_opcode_and_parameters(&self) -> (i64, usize, usize, usize) {
-        let mut digits: [u32; 5] = [0; 5];
-        let oc_str = self.program[self.pointer].to_string();
+    #[test]
+    fn test_errors() {
+        let mut program = IntCodeProgram::from_memory(vec![109, -5, 204, 1, 99]);
+        program.run();
+        assert_eq!(
+            program.status,
+            IntCodeStatus::Error(IntCodeError::InvalidAddress {
+                address: -4,
+                pointer: 2
+            })
+        );
 
-        let mut i = 0;
-        for c in oc_str.chars().rev() {
-            digits[i] = c.to_digit(10).unwrap();
-            i += 1;
+        // Parameters past the end of memory are invalid in every mode.
+        for instr in [1, 201, 1101].iter() {
+            let mut program = IntCodeProgram::from_memory(vec![1105, 1, 383]);
+            program.set_at(383, *instr);
+            program.run();
+            assert_eq!(
+                program.status,
+                IntCodeStatus::Error(IntCodeError::InvalidAddress {
+                    address: 384,
+                    pointer: 383
+                })
+            );
         }
 
-        return (
-            (digits[0] + digits[1] * 10) as i64,
-            self.get_index_from_mode(digits[2], self.pointer + 1),
-            self.get_index_from_mode(digits[3], self.pointer + 2),
-            self.get_index_from_mode(digits[4], self.pointer + 3),
+        let mut program = IntCodeProgram::from_memory(vec![1, 0, 0, 0, 42]);
+        program.run();
+        assert_eq!(
+            program.status,
+            IntCodeStatus::Error(IntCodeError::UnknownOpcode {
+                opcode: 42,
+                pointer: 4
+            })
         );
     }
 
-    fn get_index_from_mode(&self, mode: u32, idx: usize) -> usize {
-        match mode {
-            0 => {
-                if let Some(v) = self.program.get(idx) {
-                    return (*v) as usize;
-                }
-                return 0 as usize;
-            }
-            1 => return idx,
-            2 => {
-                if let Some(v) = self.program.get(idx) {
-                    return (self.rel_base as i64 + *v) as usize;
-                }
-                return 0 as usize;
-            }
-            _ => panic!("Unknown parameter mode: {}", mode),
+    #[test]
+    fn test_words() {
+        let square = 1_000_000_000_000i128;
+        let mut program = IntCodeProgram::from_words(vec![2, 5, 5, 5, 99, square]);
+        program.run();
+        assert_eq!(program.status, IntCodeStatus::Halted);
+        assert_eq!(program.get(5), square * square);
+
+        let mut program = IntCodeProgram::from_words(vec![3i32, 9, 8, 9, 10, 9, 4, 9, 99, -1, 8]);
+        program.in_buf.push(8);
+        program.run();
+        assert_eq!(program.out_buf, vec![1]);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        let mut program = IntCodeProgram::from_memory(vec![3, 9, 4, 9, 1105, 1, 0]);
+        program.in_buf.push(7);
+        program.run();
+        assert_eq!(program.status, IntCodeStatus::AwaitingInput);
+
+        let json = serde_json::to_string(&program).unwrap();
+        let mut restored: IntCodeProgram = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.snapshot(), program.snapshot());
+
+        let json = serde_json::to_string(&program.snapshot()).unwrap();
+        let snapshot: Snapshot = serde_json::from_str(&json).unwrap();
+        let mut from_snapshot = IntCodeProgram::from_memory(vec![99]);
+        from_snapshot.restore(&snapshot);
+
+        for machine in [&mut program, &mut restored, &mut from_snapshot].iter_mut() {
+            machine.in_buf.push(8);
+            machine.run();
         }
+        assert_eq!(program.out_buf, vec![7, 8]);
+        assert_eq!(restored.snapshot(), program.snapshot());
+        assert_eq!(from_snapshot.snapshot(), program.snapshot());
     }
 }
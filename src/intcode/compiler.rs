@@ -0,0 +1,805 @@
+//! Compiles a tiny C-like language down to Intcode.
+
+use std::collections::HashMap;
+use std::fmt;
+
+// Compiles a small C-like language to Intcode:
+//
+//     fn square(n) { return n * n; }
+//     fn main() {
+//         let i = input();
+//         while (i > 0) { output(square(i)); i = i - 1; }
+//     }
+//
+// Variables are integers living in the current stack frame, which is
+// addressed through the relative base. Each frame holds the return address,
+// the offset back to the caller's frame, the parameters, then locals and
+// temporaries.
+
+/// A syntax or semantic error, at the position it was found.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CompileError {
+    /// The 1-based line.
+    pub line: usize,
+    /// The 1-based column.
+    pub col: usize,
+    /// What went wrong.
+    pub message: String,
+}
+
+impl fmt::Display for CompileError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.col, self.message)
+    }
+}
+
+type Result<T> = std::result::Result<T, CompileError>;
+
+fn error<T>(pos: Pos, message: String) -> Result<T> {
+    Err(CompileError {
+        line: pos.0,
+        col: pos.1,
+        message,
+    })
+}
+
+type Pos = (usize, usize);
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Num(i64),
+    Ident(String),
+    Sym(&'static str),
+    End,
+}
+
+const SYMBOLS: [&str; 17] = [
+    "==", "!=", "<=", ">=", "(", ")", "{", "}", ",", ";", "=", "+", "-", "*", "<", ">", "!",
+];
+
+fn tokenize(source: &str) -> Result<Vec<(Token, Pos)>> {
+    let mut tokens = Vec::new();
+    for (i, line) in source.lines().enumerate() {
+        let line = match line.find("//") {
+            Some(comment) => &line[..comment],
+            None => line,
+        };
+        let chars: Vec<char> = line.chars().collect();
+        let mut col = 0;
+
+        while col < chars.len() {
+            let pos = (i + 1, col + 1);
+            let c = chars[col];
+            if c.is_whitespace() {
+                col += 1;
+            } else if c.is_ascii_digit() {
+                let start = col;
+                while col < chars.len() && chars[col].is_ascii_digit() {
+                    col += 1;
+                }
+                let digits: String = chars[start..col].iter().collect();
+                match digits.parse::<i64>() {
+                    Ok(n) => tokens.push((Token::Num(n), pos)),
+                    Err(_) => return error(pos, format!("Number out of range: {}", digits)),
+                }
+            } else if c.is_ascii_alphabetic() || c == '_' {
+                let start = col;
+                while col < chars.len() && (chars[col].is_ascii_alphanumeric() || chars[col] == '_')
+                {
+                    col += 1;
+                }
+                tokens.push((Token::Ident(chars[start..col].iter().collect()), pos));
+            } else {
+                let rest: String = chars[col..].iter().take(2).collect();
+                match SYMBOLS.iter().find(|s| rest.starts_with(**s)) {
+                    Some(sym) => {
+                        tokens.push((Token::Sym(sym), pos));
+                        col += sym.len();
+                    }
+                    None => return error(pos, format!("Unexpected character: {}", c)),
+                }
+            }
+        }
+    }
+
+    let end = (source.lines().count() + 1, 1);
+    tokens.push((Token::End, end));
+    Ok(tokens)
+}
+
+#[derive(Clone, Debug)]
+enum Expr {
+    Num(i64),
+    Var(String, Pos),
+    Input,
+    Call(String, Vec<Expr>, Pos),
+    Neg(Box<Expr>),
+    Not(Box<Expr>),
+    Binary(&'static str, Box<Expr>, Box<Expr>),
+}
+
+#[derive(Clone, Debug)]
+enum Stmt {
+    Let(String, Expr),
+    Assign(String, Expr, Pos),
+    If(Expr, Vec<Stmt>, Vec<Stmt>),
+    While(Expr, Vec<Stmt>),
+    Return(Option<Expr>),
+    Output(Expr),
+    Expr(Expr),
+}
+
+#[derive(Clone, Debug)]
+struct Function {
+    name: String,
+    params: Vec<String>,
+    body: Vec<Stmt>,
+    pos: Pos,
+}
+
+const KEYWORDS: [&str; 8] = [
+    "fn", "let", "if", "else", "while", "return", "input", "output",
+];
+
+struct Parser {
+    tokens: Vec<(Token, Pos)>,
+    idx: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> &Token {
+        &self.tokens[self.idx].0
+    }
+
+    fn pos(&self) -> Pos {
+        self.tokens[self.idx].1
+    }
+
+    fn next(&mut self) -> Token {
+        let token = self.tokens[self.idx].0.clone();
+        if token != Token::End {
+            self.idx += 1;
+        }
+        token
+    }
+
+    fn is_sym(&self, sym: &str) -> bool {
+        matches!(self.peek(), Token::Sym(s) if *s == sym)
+    }
+
+    fn is_keyword(&self, keyword: &str) -> bool {
+        matches!(self.peek(), Token::Ident(name) if name == keyword)
+    }
+
+    fn expect_sym(&mut self, sym: &str) -> Result<()> {
+        if self.is_sym(sym) {
+            self.next();
+            return Ok(());
+        }
+        error(
+            self.pos(),
+            format!("Expected '{}', found {}", sym, self.describe()),
+        )
+    }
+
+    fn expect_keyword(&mut self, keyword: &str) -> Result<()> {
+        if self.is_keyword(keyword) {
+            self.next();
+            return Ok(());
+        }
+        error(
+            self.pos(),
+            format!("Expected '{}', found {}", keyword, self.describe()),
+        )
+    }
+
+    fn expect_ident(&mut self) -> Result<String> {
+        match self.peek().clone() {
+            Token::Ident(name) if !KEYWORDS.contains(&name.as_str()) => {
+                self.next();
+                Ok(name)
+            }
+            _ => error(
+                self.pos(),
+                format!("Expected identifier, found {}", self.describe()),
+            ),
+        }
+    }
+
+    fn describe(&self) -> String {
+        match self.peek() {
+            Token::Num(n) => format!("'{}'", n),
+            Token::Ident(name) => format!("'{}'", name),
+            Token::Sym(sym) => format!("'{}'", sym),
+            Token::End => String::from("end of input"),
+        }
+    }
+
+    fn program(&mut self) -> Result<Vec<Function>> {
+        let mut functions = Vec::new();
+        while self.peek() != &Token::End {
+            functions.push(self.function()?);
+        }
+        Ok(functions)
+    }
+
+    fn function(&mut self) -> Result<Function> {
+        let pos = self.pos();
+        self.expect_keyword("fn")?;
+        let name = self.expect_ident()?;
+        self.expect_sym("(")?;
+        let mut params = Vec::new();
+        while !self.is_sym(")") {
+            if !params.is_empty() {
+                self.expect_sym(",")?;
+            }
+            params.push(self.expect_ident()?);
+        }
+        self.next();
+        let body = self.block()?;
+        Ok(Function {
+            name,
+            params,
+            body,
+            pos,
+        })
+    }
+
+    fn block(&mut self) -> Result<Vec<Stmt>> {
+        self.expect_sym("{")?;
+        let mut stmts = Vec::new();
+        while !self.is_sym("}") {
+            if self.peek() == &Token::End {
+                return error(self.pos(), String::from("Unclosed block"));
+            }
+            stmts.push(self.statement()?);
+        }
+        self.next();
+        Ok(stmts)
+    }
+
+    fn statement(&mut self) -> Result<Stmt> {
+        let stmt = if self.is_keyword("let") {
+            self.next();
+            let name = self.expect_ident()?;
+            self.expect_sym("=")?;
+            Stmt::Let(name, self.expr()?)
+        } else if self.is_keyword("if") {
+            return self.if_statement();
+        } else if self.is_keyword("while") {
+            self.next();
+            let cond = self.paren_expr()?;
+            return Ok(Stmt::While(cond, self.block()?));
+        } else if self.is_keyword("return") {
+            self.next();
+            if self.is_sym(";") {
+                Stmt::Return(None)
+            } else {
+                Stmt::Return(Some(self.expr()?))
+            }
+        } else if self.is_keyword("output") {
+            self.next();
+            Stmt::Output(self.paren_expr()?)
+        } else if matches!(self.tokens[self.idx + 1].0, Token::Sym("=")) {
+            let pos = self.pos();
+            let name = self.expect_ident()?;
+            self.next();
+            Stmt::Assign(name, self.expr()?, pos)
+        } else {
+            Stmt::Expr(self.expr()?)
+        };
+        self.expect_sym(";")?;
+        Ok(stmt)
+    }
+
+    fn if_statement(&mut self) -> Result<Stmt> {
+        self.expect_keyword("if")?;
+        let cond = self.paren_expr()?;
+        let then = self.block()?;
+        let otherwise = if !self.is_keyword("else") {
+            Vec::new()
+        } else {
+            self.next();
+            if self.is_keyword("if") {
+                vec![self.if_statement()?]
+            } else {
+                self.block()?
+            }
+        };
+        Ok(Stmt::If(cond, then, otherwise))
+    }
+
+    fn paren_expr(&mut self) -> Result<Expr> {
+        self.expect_sym("(")?;
+        let expr = self.expr()?;
+        self.expect_sym(")")?;
+        Ok(expr)
+    }
+
+    fn expr(&mut self) -> Result<Expr> {
+        self.binary(0)
+    }
+
+    fn binary(&mut self, level: usize) -> Result<Expr> {
+        const LEVELS: [&[&str]; 4] = [&["==", "!="], &["<", ">", "<=", ">="], &["+", "-"], &["*"]];
+        if level == LEVELS.len() {
+            return self.unary();
+        }
+
+        let mut lhs = self.binary(level + 1)?;
+        while let Some(op) = LEVELS[level].iter().find(|op| self.is_sym(op)) {
+            self.next();
+            let rhs = self.binary(level + 1)?;
+            lhs = Expr::Binary(op, Box::new(lhs), Box::new(rhs));
+        }
+        Ok(lhs)
+    }
+
+    fn unary(&mut self) -> Result<Expr> {
+        if self.is_sym("-") {
+            self.next();
+            return Ok(Expr::Neg(Box::new(self.unary()?)));
+        }
+        if self.is_sym("!") {
+            self.next();
+            return Ok(Expr::Not(Box::new(self.unary()?)));
+        }
+        self.primary()
+    }
+
+    fn primary(&mut self) -> Result<Expr> {
+        let pos = self.pos();
+        if self.is_sym("(") {
+            return self.paren_expr();
+        }
+        if self.is_keyword("input") {
+            self.next();
+            self.expect_sym("(")?;
+            self.expect_sym(")")?;
+            return Ok(Expr::Input);
+        }
+        if let Token::Num(n) = self.peek() {
+            let n = *n;
+            self.next();
+            return Ok(Expr::Num(n));
+        }
+
+        let name = self.expect_ident()?;
+        if !self.is_sym("(") {
+            return Ok(Expr::Var(name, pos));
+        }
+        self.next();
+        let mut args = Vec::new();
+        while !self.is_sym(")") {
+            if !args.is_empty() {
+                self.expect_sym(",")?;
+            }
+            args.push(self.expr()?);
+        }
+        self.next();
+        Ok(Expr::Call(name, args, pos))
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Val {
+    label: Option<usize>,
+    offset: i64,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum Arg {
+    Pos(Val),
+    Imm(Val),
+    Rel(Val),
+}
+
+fn num(offset: i64) -> Val {
+    Val {
+        label: None,
+        offset,
+    }
+}
+
+fn label(label: usize, offset: i64) -> Val {
+    Val {
+        label: Some(label),
+        offset,
+    }
+}
+
+const ADD: i64 = 1;
+const MUL: i64 = 2;
+const IN: i64 = 3;
+const OUT: i64 = 4;
+const JNZ: i64 = 5;
+const JZ: i64 = 6;
+const LT: i64 = 7;
+const EQ: i64 = 8;
+const ARB: i64 = 9;
+const HLT: i64 = 99;
+
+#[derive(Clone, Copy, Debug)]
+struct Signature {
+    entry: usize,
+    frame: usize,
+    back: usize,
+    arity: usize,
+}
+
+struct Generator {
+    code: Vec<Val>,
+    labels: Vec<Option<i64>>,
+    functions: HashMap<String, Signature>,
+    current: Signature,
+    scopes: Vec<HashMap<String, i64>>,
+    depth: i64,
+    frame_size: i64,
+    ret: usize,
+    ret_addr: usize,
+}
+
+impl Generator {
+    fn new_label(&mut self) -> usize {
+        self.labels.push(None);
+        self.labels.len() - 1
+    }
+
+    fn place(&mut self, label: usize) {
+        self.labels[label] = Some(self.code.len() as i64);
+    }
+
+    fn emit(&mut self, opcode: i64, args: &[Arg]) {
+        let mut instr = opcode;
+        let mut scale = 100;
+        for arg in args.iter() {
+            instr += scale
+                * match arg {
+                    Arg::Pos(_) => 0,
+                    Arg::Imm(_) => 1,
+                    Arg::Rel(_) => 2,
+                };
+            scale *= 10;
+        }
+        self.code.push(num(instr));
+        for arg in args.iter() {
+            match arg {
+                Arg::Pos(v) | Arg::Imm(v) | Arg::Rel(v) => self.code.push(*v),
+            }
+        }
+    }
+
+    fn jump(&mut self, target: usize) {
+        self.emit(JNZ, &[Arg::Imm(num(1)), Arg::Imm(label(target, 0))]);
+    }
+
+    fn copy(&mut self, from: Arg, to: Arg) {
+        self.emit(ADD, &[from, Arg::Imm(num(0)), to]);
+    }
+
+    fn temp(&mut self) -> Arg {
+        let slot = self.depth;
+        self.depth += 1;
+        self.frame_size = self.frame_size.max(self.depth);
+        Arg::Rel(num(slot))
+    }
+
+    fn lookup(&self, name: &str, pos: Pos) -> Result<Arg> {
+        for scope in self.scopes.iter().rev() {
+            if let Some(slot) = scope.get(name) {
+                return Ok(Arg::Rel(num(*slot)));
+            }
+        }
+        error(pos, format!("Undefined variable: {}", name))
+    }
+
+    fn function(&mut self, function: &Function) -> Result<()> {
+        self.current = self.functions[&function.name];
+        self.place(self.current.entry);
+        self.scopes = vec![HashMap::new()];
+        self.depth = 2;
+        for param in function.params.iter() {
+            if self.scopes[0].contains_key(param) {
+                return error(function.pos, format!("Duplicate parameter: {}", param));
+            }
+            self.scopes[0].insert(param.clone(), self.depth);
+            self.depth += 1;
+        }
+        self.frame_size = self.depth;
+
+        self.block(&function.body)?;
+        self.ret(Arg::Imm(num(0)));
+        self.labels[self.current.frame] = Some(self.frame_size);
+        self.labels[self.current.back] = Some(-self.frame_size);
+        Ok(())
+    }
+
+    fn block(&mut self, stmts: &[Stmt]) -> Result<()> {
+        let depth = self.depth;
+        self.scopes.push(HashMap::new());
+        for stmt in stmts.iter() {
+            self.statement(stmt)?;
+        }
+        self.scopes.pop();
+        self.depth = depth;
+        Ok(())
+    }
+
+    fn statement(&mut self, stmt: &Stmt) -> Result<()> {
+        let depth = self.depth;
+        match stmt {
+            Stmt::Let(name, expr) => {
+                let value = self.expr(expr)?;
+                self.depth = depth;
+                let slot = self.temp();
+                self.copy(value, slot);
+                self.scopes.last_mut().unwrap().insert(name.clone(), depth);
+                return Ok(());
+            }
+            Stmt::Assign(name, expr, pos) => {
+                let slot = self.lookup(name, *pos)?;
+                let value = self.expr(expr)?;
+                self.copy(value, slot);
+            }
+            Stmt::If(cond, then, otherwise) => {
+                let (other, end) = (self.new_label(), self.new_label());
+                let cond = self.expr(cond)?;
+                self.emit(JZ, &[cond, Arg::Imm(label(other, 0))]);
+                self.depth = depth;
+                self.block(then)?;
+                self.jump(end);
+                self.place(other);
+                self.block(otherwise)?;
+                self.place(end);
+            }
+            Stmt::While(cond, body) => {
+                let (top, end) = (self.new_label(), self.new_label());
+                self.place(top);
+                let cond = self.expr(cond)?;
+                self.emit(JZ, &[cond, Arg::Imm(label(end, 0))]);
+                self.depth = depth;
+                self.block(body)?;
+                self.jump(top);
+                self.place(end);
+            }
+            Stmt::Return(expr) => {
+                let value = match expr {
+                    Some(expr) => self.expr(expr)?,
+                    None => Arg::Imm(num(0)),
+                };
+                self.ret(value);
+            }
+            Stmt::Output(expr) => {
+                let value = self.expr(expr)?;
+                self.emit(OUT, &[value]);
+            }
+            Stmt::Expr(expr) => {
+                self.expr(expr)?;
+            }
+        }
+        self.depth = depth;
+        Ok(())
+    }
+
+    fn ret(&mut self, value: Arg) {
+        let (ret, ret_addr) = (self.ret, self.ret_addr);
+        self.copy(value, Arg::Pos(label(ret, 0)));
+        self.copy(Arg::Rel(num(0)), Arg::Pos(label(ret_addr, 0)));
+        self.emit(ARB, &[Arg::Rel(num(1))]);
+        self.emit(JNZ, &[Arg::Imm(num(1)), Arg::Pos(label(ret_addr, 0))]);
+    }
+
+    fn call(&mut self, name: &str, args: &[Expr], pos: Pos) -> Result<Arg> {
+        let callee = match self.functions.get(name) {
+            Some(callee) => *callee,
+            None => return error(pos, format!("Unknown function: {}", name)),
+        };
+        if callee.arity != args.len() {
+            return error(
+                pos,
+                format!(
+                    "{} takes {} arguments but {} were given",
+                    name,
+                    callee.arity,
+                    args.len()
+                ),
+            );
+        }
+
+        let mut values = Vec::new();
+        for arg in args.iter() {
+            values.push(self.expr(arg)?);
+        }
+
+        // The callee's frame starts right after the largest extent of ours.
+        let Signature { frame, back, .. } = self.current;
+        for (i, value) in values.into_iter().enumerate() {
+            self.copy(value, Arg::Rel(label(frame, 2 + i as i64)));
+        }
+        let resume = self.new_label();
+        self.copy(Arg::Imm(label(resume, 0)), Arg::Rel(label(frame, 0)));
+        self.copy(Arg::Imm(label(back, 0)), Arg::Rel(label(frame, 1)));
+        self.emit(ARB, &[Arg::Imm(label(frame, 0))]);
+        self.jump(callee.entry);
+        self.place(resume);
+
+        let result = self.temp();
+        self.copy(Arg::Pos(label(self.ret, 0)), result);
+        Ok(result)
+    }
+
+    fn expr(&mut self, expr: &Expr) -> Result<Arg> {
+        match expr {
+            Expr::Num(n) => Ok(Arg::Imm(num(*n))),
+            Expr::Var(name, pos) => self.lookup(name, *pos),
+            Expr::Input => {
+                let result = self.temp();
+                self.emit(IN, &[result]);
+                Ok(result)
+            }
+            Expr::Neg(expr) => {
+                let value = self.expr(expr)?;
+                let result = self.temp();
+                self.emit(MUL, &[value, Arg::Imm(num(-1)), result]);
+                Ok(result)
+            }
+            Expr::Not(expr) => {
+                let value = self.expr(expr)?;
+                let result = self.temp();
+                self.emit(EQ, &[value, Arg::Imm(num(0)), result]);
+                Ok(result)
+            }
+            Expr::Binary(op, lhs, rhs) => {
+                let lhs = self.expr(lhs)?;
+                let rhs = self.expr(rhs)?;
+                let result = self.temp();
+                match *op {
+                    "+" => self.emit(ADD, &[lhs, rhs, result]),
+                    "*" => self.emit(MUL, &[lhs, rhs, result]),
+                    "-" => {
+                        self.emit(MUL, &[rhs, Arg::Imm(num(-1)), result]);
+                        self.emit(ADD, &[lhs, result, result]);
+                    }
+                    "<" => self.emit(LT, &[lhs, rhs, result]),
+                    ">" => self.emit(LT, &[rhs, lhs, result]),
+                    "==" => self.emit(EQ, &[lhs, rhs, result]),
+                    "<=" => {
+                        self.emit(LT, &[rhs, lhs, result]);
+                        self.emit(EQ, &[result, Arg::Imm(num(0)), result]);
+                    }
+                    ">=" => {
+                        self.emit(LT, &[lhs, rhs, result]);
+                        self.emit(EQ, &[result, Arg::Imm(num(0)), result]);
+                    }
+                    "!=" => {
+                        self.emit(EQ, &[lhs, rhs, result]);
+                        self.emit(EQ, &[result, Arg::Imm(num(0)), result]);
+                    }
+                    _ => unreachable!(),
+                }
+                Ok(result)
+            }
+            Expr::Call(name, args, pos) => self.call(name, args, *pos),
+        }
+    }
+}
+
+/// Compiles a whole program, which must define `main`, to Intcode memory.
+pub fn compile(source: &str) -> std::result::Result<Vec<i64>, CompileError> {
+    let mut parser = Parser {
+        tokens: tokenize(source)?,
+        idx: 0,
+    };
+    let functions = parser.program()?;
+
+    let mut gen = Generator {
+        code: Vec::new(),
+        labels: Vec::new(),
+        functions: HashMap::new(),
+        current: Signature {
+            entry: 0,
+            frame: 0,
+            back: 0,
+            arity: 0,
+        },
+        scopes: Vec::new(),
+        depth: 0,
+        frame_size: 0,
+        ret: 0,
+        ret_addr: 0,
+    };
+    gen.ret = gen.new_label();
+    gen.ret_addr = gen.new_label();
+    let (stack, halt) = (gen.new_label(), gen.new_label());
+
+    for function in functions.iter() {
+        let signature = Signature {
+            entry: gen.new_label(),
+            frame: gen.new_label(),
+            back: gen.new_label(),
+            arity: function.params.len(),
+        };
+        if gen
+            .functions
+            .insert(function.name.clone(), signature)
+            .is_some()
+        {
+            return error(
+                function.pos,
+                format!("Duplicate function: {}", function.name),
+            );
+        }
+    }
+    let main = match gen.functions.get("main") {
+        Some(main) if main.arity == 0 => *main,
+        Some(_) => return error((1, 1), String::from("main must not take arguments")),
+        None => return error((1, 1), String::from("Missing main function")),
+    };
+
+    gen.emit(ARB, &[Arg::Imm(label(stack, 0))]);
+    gen.copy(Arg::Imm(label(halt, 0)), Arg::Rel(num(0)));
+    gen.copy(Arg::Imm(num(0)), Arg::Rel(num(1)));
+    gen.jump(main.entry);
+    gen.place(halt);
+    gen.emit(HLT, &[]);
+
+    for function in functions.iter() {
+        gen.function(function)?;
+    }
+
+    gen.place(gen.ret);
+    gen.code.push(num(0));
+    gen.place(gen.ret_addr);
+    gen.code.push(num(0));
+    gen.place(stack);
+
+    let labels = gen.labels;
+    Ok(gen
+        .code
+        .iter()
+        .map(|v| v.offset + v.label.map_or(0, |l| labels[l].unwrap()))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intcode::IntCodeProgram;
+
+    fn run(source: &str, input: Vec<i64>) -> Vec<i64> {
+        let mut program = IntCodeProgram::from_memory(compile(source).unwrap());
+        program.in_buf = input;
+        program.run();
+        program.out_buf
+    }
+
+    #[test]
+    fn test_loop() {
+        let source = "fn main() {\n\
+                      let n = input(); // countdown\n\
+                      while (n >= 1) { if (n != 2) { output(n * -3); } n = n - 1; }\n\
+                      }";
+
+        assert_eq!(run(source, vec![4]), vec![-12, -9, -3]);
+    }
+
+    #[test]
+    fn test_recursion() {
+        let source = "fn fib(n) { if (n < 2) { return n; } return fib(n - 1) + fib(n - 2); }\n\
+                      fn main() { let i = 0; while (i <= 10) { output(fib(i)); i = i + 1; } }";
+
+        assert_eq!(
+            run(source, vec![]),
+            vec![0, 1, 1, 2, 3, 5, 8, 13, 21, 34, 55]
+        );
+    }
+
+    #[test]
+    fn test_errors() {
+        let err = compile("fn main() {\n  output(x);\n}").unwrap_err();
+        assert_eq!(err.to_string(), "2:10: Undefined variable: x");
+
+        let err = compile("fn main() { let a = 1 }").unwrap_err();
+        assert_eq!(err.to_string(), "1:23: Expected ';', found '}'");
+    }
+}
@@ -0,0 +1,214 @@
+//! Memory-mapped devices that can be attached to a running program.
+
+use std::fmt;
+use std::ops::Range;
+use std::sync::{Arc, Mutex};
+
+/// Something a program can talk to by reading and writing memory. Offsets
+/// are relative to the start of the range the device is mapped over.
+pub trait Device: fmt::Debug + Send + CloneDevice {
+    /// Answers a read of the cell at `offset`.
+    fn read(&mut self, offset: usize) -> i64;
+    /// Takes a write of `value` to the cell at `offset`.
+    fn write(&mut self, offset: usize, value: i64);
+}
+
+/// A device as held by a program and the handle `map_device` returns.
+pub type SharedDevice = Arc<Mutex<dyn Device>>;
+
+/// Copies a device for a cloned program, so clones don't share its state.
+/// Every `Device` that is `Clone` gets this for free.
+pub trait CloneDevice {
+    /// A fresh shared copy of the device.
+    fn clone_device(&self) -> SharedDevice;
+}
+
+impl<D: Device + Clone + 'static> CloneDevice for D {
+    fn clone_device(&self) -> SharedDevice {
+        Arc::new(Mutex::new(self.clone()))
+    }
+}
+
+/// A device mapped over a range of memory. Cloning it, or the program it
+/// belongs to, copies the device in its current state.
+#[derive(Debug)]
+pub struct Mapping {
+    /// The addresses the device answers for.
+    pub range: Range<usize>,
+    /// The device itself.
+    pub device: SharedDevice,
+}
+
+impl Clone for Mapping {
+    fn clone(&self) -> Mapping {
+        Mapping {
+            range: self.range.clone(),
+            device: self.device.lock().unwrap().clone_device(),
+        }
+    }
+}
+
+/// A screen of `width * height` pixels stored row by row, which reads back
+/// whatever was last written.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Framebuffer {
+    /// The number of pixels in a row.
+    pub width: usize,
+    /// The number of rows.
+    pub height: usize,
+    pixels: Vec<i64>,
+}
+
+impl Framebuffer {
+    /// A blank screen.
+    pub fn new(width: usize, height: usize) -> Framebuffer {
+        Framebuffer {
+            width,
+            height,
+            pixels: vec![0; width * height],
+        }
+    }
+
+    /// The number of pixels, and so of cells to map it over.
+    pub fn len(&self) -> usize {
+        self.pixels.len()
+    }
+
+    /// Whether the screen has no pixels.
+    pub fn is_empty(&self) -> bool {
+        self.pixels.is_empty()
+    }
+
+    /// The value last written to the pixel at column `x` of row `y`.
+    pub fn pixel(&self, x: usize, y: usize) -> i64 {
+        self.pixels[y * self.width + x]
+    }
+
+    /// Draws the screen with `#` for lit pixels and spaces for zero.
+    pub fn render(&self) -> String {
+        let mut screen = String::new();
+        for row in self.pixels.chunks(self.width) {
+            for value in row.iter() {
+                screen.push(if *value == 0 { ' ' } else { '#' });
+            }
+            screen.push('\n');
+        }
+        screen
+    }
+}
+
+impl Device for Framebuffer {
+    fn read(&mut self, offset: usize) -> i64 {
+        self.pixels[offset]
+    }
+
+    fn write(&mut self, offset: usize, value: i64) {
+        self.pixels[offset] = value;
+    }
+}
+
+/// A counter that goes up by one on every read. Writing sets it.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Clock {
+    /// The value the next read returns.
+    pub ticks: i64,
+}
+
+impl Device for Clock {
+    fn read(&mut self, _: usize) -> i64 {
+        self.ticks += 1;
+        self.ticks - 1
+    }
+
+    fn write(&mut self, _: usize, value: i64) {
+        self.ticks = value;
+    }
+}
+
+/// A xorshift generator giving a new non-negative number on every read.
+/// Writing reseeds it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Random {
+    state: u64,
+}
+
+impl Random {
+    /// A generator started from `seed`; zero is treated as one.
+    pub fn new(seed: u64) -> Random {
+        Random { state: seed.max(1) }
+    }
+}
+
+impl Device for Random {
+    fn read(&mut self, _: usize) -> i64 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        (self.state >> 1) as i64
+    }
+
+    fn write(&mut self, _: usize, value: i64) {
+        self.state = (value as u64).max(1);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intcode::search::{Inputs, Search};
+    use crate::intcode::{IntCodeProgram, IntCodeStatus};
+
+    #[test]
+    fn test_framebuffer() {
+        let mut program = IntCodeProgram::from_input("1101,0,7,100,1001,100,1,103,4,103,99");
+        let framebuffer = program.map_device(100..104, Framebuffer::new(2, 2));
+        program.run();
+
+        assert_eq!(program.get(100), 7);
+        let framebuffer = framebuffer.lock().unwrap();
+        assert_eq!(framebuffer.pixel(0, 0), 7);
+        assert_eq!(framebuffer.pixel(1, 1), 8);
+        assert_eq!(program.out_buf, vec![8]);
+    }
+
+    #[test]
+    fn test_fetch() {
+        // The program's second half runs from a device: output 7, then halt.
+        let mut program = IntCodeProgram::from_input("1105,1,100");
+        let rom = program.map_device(100..104, Framebuffer::new(4, 1));
+        for (offset, value) in [104, 7, 99].iter().enumerate() {
+            rom.lock().unwrap().write(offset, *value);
+        }
+        program.run();
+
+        assert_eq!(program.status, IntCodeStatus::Halted);
+        assert_eq!(program.out_buf, vec![7]);
+    }
+
+    #[test]
+    fn test_clock() {
+        let mut program = IntCodeProgram::from_input("4,50,4,50,4,50,99");
+        program.map_device(50..51, Clock::default());
+        program.run();
+
+        assert_eq!(program.out_buf, vec![0, 1, 2]);
+
+        // Clones get their own copy of the clock, as it was when cloned.
+        let mut program = IntCodeProgram::from_input("4,50,99");
+        let clock = program.map_device(50..51, Clock { ticks: 5 });
+        let mut clone = program.clone();
+        program.run();
+        clone.run();
+
+        assert_eq!(program.out_buf, vec![5]);
+        assert_eq!(clone.out_buf, vec![5]);
+        assert_eq!(clock.lock().unwrap().ticks, 6);
+
+        // So every candidate in a search starts from the same time.
+        let mut program = IntCodeProgram::from_input("4,50,99");
+        program.map_device(50..51, Clock::default());
+        let candidates = (0..20).map(|i| Inputs(vec![i]));
+        let search = Search::new(&program).threads(4);
+        assert_eq!(search.first(candidates, |p| p.out_buf != vec![0]), None);
+    }
+}
@@ -0,0 +1,239 @@
+//! A single-threaded executor for running programs as async tasks joined by channels.
+
+use crate::intcode::{IntCodeProgram, IntCodeStatus};
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::future::Future;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::task::{Context, Poll, Wake, Waker};
+
+struct ChannelState {
+    queue: VecDeque<i64>,
+    senders: usize,
+    waker: Option<Waker>,
+}
+
+/// The sending half of a channel. Clones send to the same receiver, which
+/// sees the channel close once every sender is dropped.
+pub struct Sender {
+    state: Rc<RefCell<ChannelState>>,
+}
+
+/// The receiving half of a channel.
+pub struct Receiver {
+    state: Rc<RefCell<ChannelState>>,
+}
+
+/// An unbounded channel of values between tasks on the same executor.
+pub fn channel() -> (Sender, Receiver) {
+    let state = Rc::new(RefCell::new(ChannelState {
+        queue: VecDeque::new(),
+        senders: 1,
+        waker: None,
+    }));
+    (
+        Sender {
+            state: state.clone(),
+        },
+        Receiver { state },
+    )
+}
+
+impl Sender {
+    /// Queues `value`, waking the receiver if it's waiting.
+    pub fn send(&self, value: i64) {
+        let mut state = self.state.borrow_mut();
+        state.queue.push_back(value);
+        if let Some(waker) = state.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+impl Clone for Sender {
+    fn clone(&self) -> Sender {
+        self.state.borrow_mut().senders += 1;
+        Sender {
+            state: self.state.clone(),
+        }
+    }
+}
+
+impl Drop for Sender {
+    fn drop(&mut self) {
+        let mut state = self.state.borrow_mut();
+        state.senders -= 1;
+        if state.senders == 0 {
+            if let Some(waker) = state.waker.take() {
+                waker.wake();
+            }
+        }
+    }
+}
+
+impl Receiver {
+    /// Waits for the next value, or None once the channel is empty and
+    /// closed.
+    pub fn recv(&self) -> Recv<'_> {
+        Recv { receiver: self }
+    }
+
+    /// The next value if one is already queued.
+    pub fn try_recv(&self) -> Option<i64> {
+        self.state.borrow_mut().queue.pop_front()
+    }
+}
+
+/// The future returned by `Receiver::recv`.
+pub struct Recv<'a> {
+    receiver: &'a Receiver,
+}
+
+impl Future for Recv<'_> {
+    type Output = Option<i64>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<i64>> {
+        let mut state = self.receiver.state.borrow_mut();
+        if let Some(value) = state.queue.pop_front() {
+            Poll::Ready(Some(value))
+        } else if state.senders == 0 {
+            Poll::Ready(None)
+        } else {
+            state.waker = Some(cx.waker().clone());
+            Poll::Pending
+        }
+    }
+}
+
+/// Runs the program until it halts, suspending whenever it waits for input.
+/// Returns the program once it stops for any other reason or the input
+/// channel closes.
+pub async fn run(mut program: IntCodeProgram, input: Receiver, output: Sender) -> IntCodeProgram {
+    loop {
+        program.run();
+        for value in program.out_buf.drain(..) {
+            output.send(value);
+        }
+
+        if program.status != IntCodeStatus::AwaitingInput {
+            return program;
+        }
+        match input.recv().await {
+            Some(value) => program.in_buf.push(value),
+            None => return program,
+        }
+    }
+}
+
+struct Flag(AtomicBool);
+
+impl Wake for Flag {
+    fn wake(self: Arc<Self>) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+}
+
+struct Task {
+    future: Pin<Box<dyn Future<Output = ()>>>,
+    woken: Arc<Flag>,
+}
+
+/// The result of a spawned task.
+pub struct JoinHandle<T> {
+    result: Rc<RefCell<Option<T>>>,
+}
+
+impl<T> JoinHandle<T> {
+    /// The task's result, or None if it hasn't finished, for instance because
+    /// `Executor::run` gave up with it still blocked.
+    pub fn take(&self) -> Option<T> {
+        self.result.borrow_mut().take()
+    }
+}
+
+/// Runs tasks on the current thread, polling each only when it's woken.
+#[derive(Default)]
+pub struct Executor {
+    tasks: Vec<Task>,
+}
+
+impl Executor {
+    /// An executor with no tasks.
+    pub fn new() -> Executor {
+        Executor { tasks: Vec::new() }
+    }
+
+    /// Adds a task, which first runs on the next `run`.
+    pub fn spawn<F, T>(&mut self, future: F) -> JoinHandle<T>
+    where
+        F: Future<Output = T> + 'static,
+        T: 'static,
+    {
+        let result = Rc::new(RefCell::new(None));
+        let slot = result.clone();
+        self.tasks.push(Task {
+            future: Box::pin(async move {
+                *slot.borrow_mut() = Some(future.await);
+            }),
+            woken: Arc::new(Flag(AtomicBool::new(true))),
+        });
+        JoinHandle { result }
+    }
+
+    /// Polls woken tasks until every task has finished or none can make
+    /// progress, and returns the number of tasks left blocked.
+    pub fn run(&mut self) -> usize {
+        loop {
+            let mut progressed = false;
+            let mut i = 0;
+            while i < self.tasks.len() {
+                if !self.tasks[i].woken.0.swap(false, Ordering::Relaxed) {
+                    i += 1;
+                    continue;
+                }
+
+                progressed = true;
+                let waker = Waker::from(self.tasks[i].woken.clone());
+                let mut cx = Context::from_waker(&waker);
+                if self.tasks[i].future.as_mut().poll(&mut cx).is_ready() {
+                    self.tasks.remove(i);
+                } else {
+                    i += 1;
+                }
+            }
+
+            if !progressed {
+                return self.tasks.len();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pipeline() {
+        let mut executor = Executor::new();
+        let (input, rx) = channel();
+        let (tx, output) = channel();
+        let doubler = IntCodeProgram::from_input("3,11,102,2,11,11,4,11,1105,1,0");
+        executor.spawn(run(doubler, rx, tx));
+
+        let collected = executor.spawn(async move {
+            let mut collected = Vec::new();
+            for value in 1..4 {
+                input.send(value);
+                collected.push(output.recv().await.unwrap());
+            }
+            collected
+        });
+
+        assert_eq!(executor.run(), 0);
+        assert_eq!(collected.take(), Some(vec![2, 4, 6]));
+    }
+}
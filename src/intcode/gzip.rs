@@ -0,0 +1,283 @@
+//! Minimal gzip (RFC 1952) reader with a DEFLATE (RFC 1951) decoder, modelled
+//! on zlib's puff.c.
+
+const LENGTH_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+const LENGTH_EXTRA: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+const DIST_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+const DIST_EXTRA: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+const CODE_LENGTH_ORDER: [usize; 19] = [
+    16, 17, 18, 0, 8, 7, 9, 6, 10, 5, 11, 4, 12, 3, 13, 2, 14, 1, 15,
+];
+
+pub fn is_gzip(data: &[u8]) -> bool {
+    data.starts_with(&[0x1f, 0x8b])
+}
+
+pub fn decompress(data: &[u8]) -> Result<Vec<u8>, String> {
+    if !is_gzip(data) || data.len() < 18 {
+        return Err(String::from("Not a gzip file"));
+    }
+    if data[2] != 8 {
+        return Err(format!("Unsupported compression method: {}", data[2]));
+    }
+
+    let flags = data[3];
+    let mut pos = 10;
+    if flags & 4 != 0 {
+        let len = *data.get(pos).ok_or("Truncated header")? as usize
+            | (*data.get(pos + 1).ok_or("Truncated header")? as usize) << 8;
+        pos += 2 + len;
+    }
+    for flag in [8, 16].iter() {
+        if flags & flag != 0 {
+            while *data.get(pos).ok_or("Truncated header")? != 0 {
+                pos += 1;
+            }
+            pos += 1;
+        }
+    }
+    if flags & 2 != 0 {
+        pos += 2;
+    }
+
+    let mut reader = BitReader {
+        data: data.get(pos..).ok_or("Truncated header")?,
+        pos: 0,
+        buf: 0,
+        count: 0,
+    };
+    let output = inflate(&mut reader)?;
+
+    let trailer = &reader.data[reader.pos..];
+    if trailer.len() < 8 {
+        return Err(String::from("Truncated trailer"));
+    }
+    let word =
+        |i: usize| u32::from_le_bytes([trailer[i], trailer[i + 1], trailer[i + 2], trailer[i + 3]]);
+    if word(0) != crc32(&output) {
+        return Err(String::from("CRC mismatch"));
+    }
+    if word(4) != output.len() as u32 {
+        return Err(String::from("Length mismatch"));
+    }
+    Ok(output)
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for byte in data.iter() {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+struct BitReader<'a> {
+    data: &'a [u8],
+    pos: usize,
+    buf: u32,
+    count: u32,
+}
+
+impl BitReader<'_> {
+    fn bits(&mut self, need: u32) -> Result<u32, String> {
+        while self.count < need {
+            let byte = *self.data.get(self.pos).ok_or("Unexpected end of data")?;
+            self.buf |= (byte as u32) << self.count;
+            self.pos += 1;
+            self.count += 8;
+        }
+        let value = self.buf & ((1u64 << need) - 1) as u32;
+        self.buf >>= need;
+        self.count -= need;
+        Ok(value)
+    }
+
+    fn align(&mut self) {
+        self.buf = 0;
+        self.count = 0;
+    }
+}
+
+struct Huffman {
+    counts: [u16; 16],
+    symbols: Vec<u16>,
+}
+
+impl Huffman {
+    fn new(lengths: &[u8]) -> Huffman {
+        let mut counts = [0u16; 16];
+        for len in lengths.iter() {
+            counts[*len as usize] += 1;
+        }
+        counts[0] = 0;
+
+        let mut offsets = [0u16; 16];
+        for len in 1..15 {
+            offsets[len + 1] = offsets[len] + counts[len];
+        }
+        let mut symbols = vec![0; lengths.len()];
+        for (symbol, len) in lengths.iter().enumerate() {
+            if *len != 0 {
+                symbols[offsets[*len as usize] as usize] = symbol as u16;
+                offsets[*len as usize] += 1;
+            }
+        }
+        Huffman { counts, symbols }
+    }
+
+    fn decode(&self, reader: &mut BitReader) -> Result<u16, String> {
+        let (mut code, mut first, mut index) = (0i32, 0i32, 0i32);
+        for len in 1..16 {
+            code |= reader.bits(1)? as i32;
+            let count = self.counts[len] as i32;
+            if code - count < first {
+                return Ok(self.symbols[(index + (code - first)) as usize]);
+            }
+            index += count;
+            first = (first + count) << 1;
+            code <<= 1;
+        }
+        Err(String::from("Invalid Huffman code"))
+    }
+}
+
+fn inflate(reader: &mut BitReader) -> Result<Vec<u8>, String> {
+    let mut output = Vec::new();
+    loop {
+        let last = reader.bits(1)?;
+        match reader.bits(2)? {
+            0 => stored(reader, &mut output)?,
+            1 => {
+                let mut lengths = [0u8; 288];
+                for (symbol, len) in lengths.iter_mut().enumerate() {
+                    *len = match symbol {
+                        0..=143 => 8,
+                        144..=255 => 9,
+                        256..=279 => 7,
+                        _ => 8,
+                    };
+                }
+                let (lit, dist) = (Huffman::new(&lengths), Huffman::new(&[5; 30]));
+                codes(reader, &mut output, &lit, &dist)?;
+            }
+            2 => {
+                let (lit, dist) = dynamic_tables(reader)?;
+                codes(reader, &mut output, &lit, &dist)?;
+            }
+            _ => return Err(String::from("Invalid block type")),
+        }
+        if last == 1 {
+            return Ok(output);
+        }
+    }
+}
+
+fn stored(reader: &mut BitReader, output: &mut Vec<u8>) -> Result<(), String> {
+    reader.align();
+    let header = reader
+        .data
+        .get(reader.pos..reader.pos + 4)
+        .ok_or("Unexpected end of data")?;
+    let len = u16::from_le_bytes([header[0], header[1]]);
+    if len != !u16::from_le_bytes([header[2], header[3]]) {
+        return Err(String::from("Stored block length mismatch"));
+    }
+    reader.pos += 4;
+    let block = reader
+        .data
+        .get(reader.pos..reader.pos + len as usize)
+        .ok_or("Unexpected end of data")?;
+    output.extend_from_slice(block);
+    reader.pos += len as usize;
+    Ok(())
+}
+
+fn dynamic_tables(reader: &mut BitReader) -> Result<(Huffman, Huffman), String> {
+    let lit_count = reader.bits(5)? as usize + 257;
+    let dist_count = reader.bits(5)? as usize + 1;
+    let code_count = reader.bits(4)? as usize + 4;
+
+    let mut code_lengths = [0u8; 19];
+    for idx in CODE_LENGTH_ORDER.iter().take(code_count) {
+        code_lengths[*idx] = reader.bits(3)? as u8;
+    }
+    let code_huffman = Huffman::new(&code_lengths);
+
+    let mut lengths = Vec::new();
+    while lengths.len() < lit_count + dist_count {
+        let (value, repeat) = match code_huffman.decode(reader)? {
+            symbol @ 0..=15 => (symbol as u8, 1),
+            16 => match lengths.last() {
+                Some(prev) => (*prev, 3 + reader.bits(2)?),
+                None => return Err(String::from("Repeat with no previous length")),
+            },
+            17 => (0, 3 + reader.bits(3)?),
+            _ => (0, 11 + reader.bits(7)?),
+        };
+        for _ in 0..repeat {
+            lengths.push(value);
+        }
+    }
+    if lengths.len() > lit_count + dist_count {
+        return Err(String::from("Too many code lengths"));
+    }
+
+    Ok((
+        Huffman::new(&lengths[..lit_count]),
+        Huffman::new(&lengths[lit_count..]),
+    ))
+}
+
+fn codes(
+    reader: &mut BitReader,
+    output: &mut Vec<u8>,
+    lit: &Huffman,
+    dist: &Huffman,
+) -> Result<(), String> {
+    loop {
+        let symbol = lit.decode(reader)? as usize;
+        match symbol {
+            0..=255 => output.push(symbol as u8),
+            256 => return Ok(()),
+            _ => {
+                let idx = symbol - 257;
+                if idx >= LENGTH_BASE.len() {
+                    return Err(String::from("Invalid length symbol"));
+                }
+                let len =
+                    LENGTH_BASE[idx] as usize + reader.bits(LENGTH_EXTRA[idx] as u32)? as usize;
+
+                let idx = dist.decode(reader)? as usize;
+                if idx >= DIST_BASE.len() {
+                    return Err(String::from("Invalid distance symbol"));
+                }
+                let distance =
+                    DIST_BASE[idx] as usize + reader.bits(DIST_EXTRA[idx] as u32)? as usize;
+                if distance > output.len() {
+                    return Err(String::from("Distance too far back"));
+                }
+                for _ in 0..len {
+                    output.push(output[output.len() - distance]);
+                }
+            }
+        }
+    }
+}
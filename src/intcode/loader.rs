@@ -0,0 +1,240 @@
+//! Reading and writing programs as text, gzip-compressed text or binary.
+
+use crate::intcode::gzip;
+use std::fmt;
+use std::fs;
+use std::io;
+
+/// The header that starts every binary program.
+pub const BINARY_MAGIC: &[u8; 4] = b"ICB1";
+
+/// Where and why text couldn't be read as a program.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParseError {
+    /// The line number, counting from 1.
+    pub line: usize,
+    /// The column number, counting from 1.
+    pub col: usize,
+    /// The problem found there.
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.col, self.message)
+    }
+}
+
+/// Why a program couldn't be loaded.
+#[derive(Debug)]
+pub enum LoadError {
+    /// The file couldn't be read or written.
+    Io(io::Error),
+    /// The file is text but not a valid program.
+    Parse(ParseError),
+    /// The file is a malformed binary or gzip stream, or not UTF-8.
+    Format(String),
+}
+
+impl fmt::Display for LoadError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            LoadError::Io(e) => write!(f, "{}", e),
+            LoadError::Parse(e) => write!(f, "{}", e),
+            LoadError::Format(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl From<io::Error> for LoadError {
+    fn from(e: io::Error) -> LoadError {
+        LoadError::Io(e)
+    }
+}
+
+impl ParseError {
+    fn new(line: usize, col: usize, message: &str) -> ParseError {
+        ParseError {
+            line: line + 1,
+            col: col + 1,
+            message: String::from(message),
+        }
+    }
+}
+
+/// Parses a text program. Values may be separated by commas, whitespace or
+/// both, and `#` starts a comment running to the end of the line. Every comma
+/// must sit between two values, though these may be on different lines.
+pub fn parse(input: &str) -> Result<Vec<i64>, ParseError> {
+    let mut program = Vec::new();
+    // The line and column of a comma still waiting for the value after it.
+    let mut pending_comma = None;
+
+    for (i, line) in input.lines().enumerate() {
+        let line = match line.find('#') {
+            Some(comment) => &line[..comment],
+            None => line,
+        };
+
+        let chars: Vec<char> = line.chars().collect();
+        let mut col = 0;
+        while col < chars.len() {
+            let c = chars[col];
+            if c.is_whitespace() {
+                col += 1;
+            } else if c == ',' {
+                if pending_comma.is_some() {
+                    return Err(ParseError::new(i, col, "Missing value between commas"));
+                }
+                if program.is_empty() {
+                    return Err(ParseError::new(i, col, "Missing value before comma"));
+                }
+                pending_comma = Some((i, col));
+                col += 1;
+            } else {
+                let start = col;
+                while col < chars.len() && chars[col] != ',' && !chars[col].is_whitespace() {
+                    col += 1;
+                }
+                let token: String = chars[start..col].iter().collect();
+                match token.parse::<i64>() {
+                    Ok(value) => program.push(value),
+                    Err(_) => {
+                        let message = format!("Invalid value: {}", token);
+                        return Err(ParseError::new(i, start, &message));
+                    }
+                }
+                pending_comma = None;
+            }
+        }
+    }
+
+    match pending_comma {
+        Some((line, col)) => Err(ParseError::new(line, col, "Missing value after comma")),
+        None => Ok(program),
+    }
+}
+
+/// Encodes memory as `BINARY_MAGIC` followed by little-endian 64-bit values.
+pub fn to_binary(memory: &[i64]) -> Vec<u8> {
+    let mut bytes = BINARY_MAGIC.to_vec();
+    for value in memory.iter() {
+        bytes.extend_from_slice(&value.to_le_bytes());
+    }
+    bytes
+}
+
+/// Decodes memory written by `to_binary`.
+pub fn from_binary(bytes: &[u8]) -> Result<Vec<i64>, LoadError> {
+    let body = match bytes.strip_prefix(BINARY_MAGIC) {
+        Some(body) if body.len() % 8 == 0 => body,
+        Some(_) => return Err(LoadError::Format(String::from("Truncated binary program"))),
+        None => return Err(LoadError::Format(String::from("Missing binary header"))),
+    };
+
+    Ok(body
+        .chunks(8)
+        .map(|chunk| {
+            let mut word = [0; 8];
+            word.copy_from_slice(chunk);
+            i64::from_le_bytes(word)
+        })
+        .collect())
+}
+
+/// Reads a program in any supported format, telling them apart by their
+/// headers: gzip, then binary, then text.
+pub fn from_bytes(bytes: &[u8]) -> Result<Vec<i64>, LoadError> {
+    if gzip::is_gzip(bytes) {
+        return from_bytes(&gzip::decompress(bytes).map_err(LoadError::Format)?);
+    }
+    if bytes.starts_with(BINARY_MAGIC) {
+        return from_binary(bytes);
+    }
+
+    match std::str::from_utf8(bytes) {
+        Ok(text) => parse(text).map_err(LoadError::Parse),
+        Err(_) => Err(LoadError::Format(String::from(
+            "Program is not valid UTF-8",
+        ))),
+    }
+}
+
+/// Reads a program file in any supported format.
+pub fn load(filename: &str) -> Result<Vec<i64>, LoadError> {
+    from_bytes(&fs::read(filename)?)
+}
+
+/// Writes memory to a binary program file, leaving off trailing zeros.
+pub fn save(filename: &str, memory: &[i64]) -> Result<(), LoadError> {
+    let len = memory.iter().rposition(|v| *v != 0).map_or(0, |i| i + 1);
+    fs::write(filename, to_binary(&memory[..len]))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn from_hex(hex: &str) -> Vec<u8> {
+        (0..hex.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&hex[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_parse() {
+        let input = "# day two\n1,9,10,3,\n2, 3, 11, 0  # multiply\n99\n30 40 50\n";
+        assert_eq!(
+            parse(input),
+            Ok(vec![1, 9, 10, 3, 2, 3, 11, 0, 99, 30, 40, 50])
+        );
+
+        let err = parse("1,2,3\n4,,5").unwrap_err();
+        assert_eq!(err.to_string(), "2:3: Missing value between commas");
+        let err = parse("1,2,3\n 4,x5").unwrap_err();
+        assert_eq!(err.to_string(), "2:4: Invalid value: x5");
+
+        let err = parse("1,\n# nothing here\n,2").unwrap_err();
+        assert_eq!(err.to_string(), "3:1: Missing value between commas");
+        let err = parse("\n ,1,2").unwrap_err();
+        assert_eq!(err.to_string(), "2:2: Missing value before comma");
+        let err = parse("1,2,\n\n").unwrap_err();
+        assert_eq!(err.to_string(), "1:4: Missing value after comma");
+    }
+
+    #[test]
+    fn test_binary() {
+        let memory = vec![1, -2, i64::MAX, 99];
+        assert_eq!(from_bytes(&to_binary(&memory)).unwrap(), memory);
+    }
+
+    #[test]
+    fn test_gzip() {
+        let fixed = from_hex(
+            "1f8b080000000000020333d43134d231d231d6310442086dac6302a64d750c80b4a5251700d6f5\
+             d27124000000",
+        );
+        assert_eq!(
+            from_bytes(&fixed).unwrap(),
+            vec![1, 12, 2, 3, 1, 1, 2, 3, 1, 3, 4, 3, 1, 5, 0, 3, 99]
+        );
+
+        let dynamic = from_hex(
+            "1f8b08000000000002032d92419205210843f77316161f1590fb5f6c5e6c165d16360949f067ed\
+             6d5969abdcea77ac2bec1edb6ed7d3ce6eebdfcfbcdbf64eaedd7c1d2bba561e8b0615c9070b7d\
+             abdaaad33cdcf681467d9c4d7db8bffc77f1d12f18f09d4377cc5d25b76d87a6cb7047c4428cdf\
+             4f5a2211a51bc581f2a06d7336f5e1fe8e83a6fd020be0fb630b58730be6b6c682337ba3e1a065\
+             c7272d91d82ab91e07ce59d4de9af2390dda37b0167c2c34b40d7d29a9fe925b1aff9264ec9808\
+             545ee54d5088ffe1a8892e99ed80660934a7408073d6d02255420a6a3c30dbd1e0d2325b40e246\
+             6a2039c60287cadb8af7f97ccd80048e3170c509f7d9f9662937cdaea7819963e0a0515aabf269\
+             d71ae4242a9fb1afabe896ed7e3bfcd814cb12bba68c8366ba62bc912f56a97b314b2ca2733cb4\
+             4e79ea7c5b92556dadb53df0392e0a5e6dd9b5ed59839e52be2771c6c3c6839ecc293debfefb07\
+             d4fb7b7b0b030000",
+        );
+        let program = from_bytes(&dynamic).unwrap();
+        assert_eq!(program.len(), 200);
+        assert_eq!(program.iter().sum::<i64>(), 105300);
+    }
+}
@@ -0,0 +1,235 @@
+//! Snapshots, disassembly, memory dumps and snapshot diffs.
+
+use crate::intcode::opcodes::OpcodeTable;
+use crate::intcode::word::Word;
+use crate::intcode::IntCodeStatus;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+
+/// A copy of a machine's state, taken by `IntCodeProgram::snapshot`.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Snapshot<W: Word = i64> {
+    /// Every cell of memory.
+    pub memory: Vec<W>,
+    /// The instruction pointer.
+    pub pointer: usize,
+    /// The relative base.
+    pub rel_base: W,
+    /// The steps executed when the snapshot was taken.
+    pub steps: u64,
+    /// Unread input.
+    pub in_buf: Vec<W>,
+    /// Output not yet taken.
+    pub out_buf: Vec<W>,
+    /// The machine's status.
+    pub status: IntCodeStatus,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+/// How `dump` prints values.
+pub enum Radix {
+    /// Base 10.
+    Decimal,
+    /// Base 16, with a minus sign for negative values.
+    Hex,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+/// One decoded instruction, or a single cell of data.
+pub struct Instruction {
+    /// Where it starts.
+    pub addr: usize,
+    /// How many cells it takes, including its parameters.
+    pub len: usize,
+    /// The mnemonic and parameters, or `data` and the value.
+    pub text: String,
+}
+
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:04}: {}", self.addr, self.text)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+/// A cell that differs between two snapshots.
+pub struct Change {
+    /// The cell's address.
+    pub addr: usize,
+    /// Its value in the older snapshot.
+    pub old: i64,
+    /// Its value in the newer snapshot.
+    pub new: i64,
+    /// The instruction the cell was part of in the older snapshot's listing.
+    pub instruction: Option<Instruction>,
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:04}: {} -> {}", self.addr, self.old, self.new)?;
+        if let Some(instruction) = &self.instruction {
+            write!(f, "  ({})", instruction)?;
+        }
+        Ok(())
+    }
+}
+
+/// Decodes the instruction at `addr`, or a cell of data if its opcode is
+/// unknown or its parameters run past the end of memory.
+pub fn disassemble_at(memory: &[i64], opcodes: &OpcodeTable, addr: usize) -> Instruction {
+    let value = memory[addr];
+    let opcode = match opcodes.get(value % 100) {
+        Some(opcode) if addr + opcode.params < memory.len() => opcode,
+        _ => {
+            return Instruction {
+                addr,
+                len: 1,
+                text: format!("data {}", value),
+            }
+        }
+    };
+
+    let mut modes = value / 100;
+    let mut params = Vec::new();
+    for i in 0..opcode.params {
+        let param = memory[addr + i + 1];
+        params.push(match modes % 10 {
+            0 => format!("[{}]", param),
+            1 => format!("#{}", param),
+            2 if param < 0 => format!("[rb{}]", param),
+            2 => format!("[rb+{}]", param),
+            _ => format!("?{}", param),
+        });
+        modes /= 10;
+    }
+
+    let mut text = String::from(opcode.name);
+    if !params.is_empty() {
+        text.push(' ');
+        text.push_str(&params.join(", "));
+    }
+    Instruction {
+        addr,
+        len: opcode.params + 1,
+        text,
+    }
+}
+
+/// Disassembles memory up to its last non-zero cell, by a linear sweep
+/// from address zero. Anything that does not decode is data.
+pub fn disassemble(memory: &[i64], opcodes: &OpcodeTable) -> Vec<Instruction> {
+    let memory = trimmed(memory);
+    let mut instructions = Vec::new();
+    let mut addr = 0;
+    while addr < memory.len() {
+        let instruction = disassemble_at(memory, opcodes, addr);
+        addr += instruction.len;
+        instructions.push(instruction);
+    }
+    instructions
+}
+
+/// Prints memory up to its last non-zero cell, `width` values to a row,
+/// with each row annotated by the instructions starting in it.
+pub fn dump(memory: &[i64], opcodes: &OpcodeTable, radix: Radix, width: usize) -> String {
+    let memory = trimmed(memory);
+    let listing = disassemble(memory, opcodes);
+    let format = |value: i64| match radix {
+        Radix::Decimal => format!("{:>6}", value),
+        Radix::Hex if value < 0 => format!("{:>6}", format!("-{:x}", -(value as i128))),
+        Radix::Hex => format!("{:>6x}", value),
+    };
+
+    let mut table = String::new();
+    for (row, values) in memory.chunks(width.max(1)).enumerate() {
+        let start = row * width.max(1);
+        table.push_str(&format!("{:04}:", start));
+        for value in values.iter() {
+            table.push_str(&format(*value));
+        }
+
+        let annotations: Vec<&str> = listing
+            .iter()
+            .filter(|i| i.addr >= start && i.addr < start + values.len())
+            .map(|i| i.text.as_str())
+            .collect();
+        if !annotations.is_empty() {
+            for _ in values.len()..width {
+                table.push_str("      ");
+            }
+            table.push_str("  ; ");
+            table.push_str(&annotations.join("; "));
+        }
+        table.push('\n');
+    }
+    table
+}
+
+/// Every cell that changed between two snapshots, in address order. Memory
+/// one snapshot lacks counts as zero.
+pub fn diff(old: &Snapshot, new: &Snapshot, opcodes: &OpcodeTable) -> Vec<Change> {
+    let listing = disassemble(&old.memory, opcodes);
+    let len = old.memory.len().max(new.memory.len());
+
+    (0..len)
+        .filter_map(|addr| {
+            let before = *old.memory.get(addr).unwrap_or(&0);
+            let after = *new.memory.get(addr).unwrap_or(&0);
+            if before == after {
+                return None;
+            }
+            Some(Change {
+                addr,
+                old: before,
+                new: after,
+                instruction: listing.iter().rev().find(|i| i.addr <= addr).cloned(),
+            })
+        })
+        .collect()
+}
+
+fn trimmed(memory: &[i64]) -> &[i64] {
+    let len = memory.iter().rposition(|v| *v != 0).map_or(0, |i| i + 1);
+    &memory[..len]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intcode::IntCodeProgram;
+    use alloc::string::ToString;
+    use alloc::vec;
+
+    #[test]
+    fn test_dump() {
+        let program = IntCodeProgram::from_memory(vec![1002, 4, 3, 4, 33, -1]);
+        let table = dump(&program.snapshot().memory, program.opcodes(), Radix::Hex, 4);
+
+        assert_eq!(
+            table,
+            "0000:   3ea     4     3     4  ; mul [4], #3, [4]\n\
+             0004:    21    -1              ; data 33; data -1\n"
+        );
+    }
+
+    #[test]
+    fn test_diff() {
+        let mut program = IntCodeProgram::from_memory(vec![3, 9, 1002, 9, 3, 9, 4, 9, 99]);
+        let before = program.snapshot();
+        program.in_buf.push(5);
+        program.run();
+        let changes = diff(&before, &program.snapshot(), program.opcodes());
+
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].to_string(), "0009: 0 -> 15  (0008: hlt)");
+
+        program.restore(&before);
+        assert_eq!(program.snapshot(), before);
+    }
+}
@@ -0,0 +1,169 @@
+//! The instruction set, as a table of handlers that can be extended.
+
+use crate::intcode::word::Word;
+use crate::intcode::IntCodeProgram;
+use alloc::vec;
+use alloc::vec::Vec;
+
+/// The most parameters an opcode can take.
+pub const MAX_PARAMS: usize = 8;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+/// What a handler tells the machine to do next.
+pub enum Step {
+    /// Move past the instruction and its parameters.
+    Next,
+    /// Continue from the given address.
+    Jump(usize),
+    /// Stop awaiting input, to retry the instruction once there is some.
+    Wait,
+    /// Stop for good.
+    Halt,
+}
+
+/// Executes an opcode, given the resolved address of each parameter.
+pub type Handler<W = i64> = fn(&mut IntCodeProgram<W>, &[usize]) -> Step;
+
+/// An entry in the opcode table.
+#[derive(Debug)]
+pub struct Opcode<W: Word = i64> {
+    /// The mnemonic used in disassembly.
+    pub name: &'static str,
+    /// How many parameters follow the opcode.
+    pub params: usize,
+    /// What the opcode does.
+    pub handler: Handler<W>,
+}
+
+// Derived impls would require `W: Copy`, which bignum words are not.
+impl<W: Word> Clone for Opcode<W> {
+    fn clone(&self) -> Opcode<W> {
+        *self
+    }
+}
+
+impl<W: Word> Copy for Opcode<W> {}
+
+/// The opcodes a machine understands, indexed by their two-digit codes.
+#[derive(Clone, Debug)]
+pub struct OpcodeTable<W: Word = i64> {
+    opcodes: Vec<Option<Opcode<W>>>,
+}
+
+impl<W: Word> OpcodeTable<W> {
+    /// A table with no opcodes at all.
+    pub fn empty() -> OpcodeTable<W> {
+        OpcodeTable {
+            opcodes: vec![None; 100],
+        }
+    }
+
+    /// The full instruction set from the puzzles.
+    pub fn standard() -> OpcodeTable<W> {
+        let mut table = OpcodeTable::empty();
+        table
+            .register(1, "add", 3, |p, a| p.opcode_one(a[0], a[1], a[2]))
+            .register(2, "mul", 3, |p, a| p.opcode_two(a[0], a[1], a[2]))
+            .register(3, "in", 1, |p, a| p.opcode_three(a[0]))
+            .register(4, "out", 1, |p, a| p.opcode_four(a[0]))
+            .register(5, "jnz", 2, |p, a| p.opcode_five(a[0], a[1]))
+            .register(6, "jz", 2, |p, a| p.opcode_six(a[0], a[1]))
+            .register(7, "lt", 3, |p, a| p.opcode_seven(a[0], a[1], a[2]))
+            .register(8, "eq", 3, |p, a| p.opcode_eight(a[0], a[1], a[2]))
+            .register(9, "arb", 1, |p, a| p.opcode_nine(a[0]))
+            .register(99, "hlt", 0, |p, _| p.opcode_ninety_nine());
+        table
+    }
+
+    /// Only the opcodes the puzzles had introduced by the given day.
+    pub fn introduced_by(day: u32) -> OpcodeTable<W> {
+        let mut table = OpcodeTable::standard();
+        if day < 9 {
+            table.disable(9);
+        }
+        if day < 5 {
+            for code in 3..9 {
+                table.disable(code);
+            }
+        }
+        table
+    }
+
+    /// Adds or replaces an opcode. Panics if the code isn't two digits or it
+    /// takes more than `MAX_PARAMS` parameters.
+    pub fn register(
+        &mut self,
+        code: i64,
+        name: &'static str,
+        params: usize,
+        handler: Handler<W>,
+    ) -> &mut OpcodeTable<W> {
+        assert!((0..100).contains(&code), "Opcode out of range: {}", code);
+        assert!(params <= MAX_PARAMS, "Too many parameters: {}", params);
+        self.opcodes[code as usize] = Some(Opcode {
+            name,
+            params,
+            handler,
+        });
+        self
+    }
+
+    /// Removes an opcode, so executing it faults.
+    pub fn disable(&mut self, code: i64) -> &mut OpcodeTable<W> {
+        if let Some(opcode) = self.opcodes.get_mut(code as usize) {
+            *opcode = None;
+        }
+        self
+    }
+
+    /// The opcode with the given code, if there is one.
+    pub fn get(&self, code: i64) -> Option<&Opcode<W>> {
+        if code < 0 {
+            return None;
+        }
+        self.opcodes.get(code as usize)?.as_ref()
+    }
+}
+
+impl<W: Word> Default for OpcodeTable<W> {
+    fn default() -> OpcodeTable<W> {
+        OpcodeTable::standard()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intcode::{IntCodeError, IntCodeStatus};
+    use alloc::vec;
+
+    #[test]
+    fn test_custom_opcode() {
+        let mut table = OpcodeTable::standard();
+        table.register(10, "sq", 2, |p, a| {
+            p.set_at(a[1], p.get(a[0]) * p.get(a[0]));
+            Step::Next
+        });
+        let mut program = IntCodeProgram::from_memory(vec![3, 9, 10, 9, 9, 4, 9, 99, 0, 0]);
+        program.set_opcodes(table);
+        program.in_buf.push(12);
+        program.run();
+
+        assert_eq!(program.out_buf, vec![144]);
+    }
+
+    #[test]
+    fn test_introduced_by() {
+        let mut program = IntCodeProgram::from_memory(vec![3, 0, 99]);
+        program.set_opcodes(OpcodeTable::introduced_by(2));
+        program.run();
+
+        assert_eq!(
+            program.status,
+            IntCodeStatus::Error(IntCodeError::UnknownOpcode {
+                opcode: 3,
+                pointer: 0
+            })
+        );
+    }
+}
@@ -0,0 +1,196 @@
+//! Parallel searches over program patches or inputs.
+
+use crate::intcode::IntCodeProgram;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+/// One thing to try in a search, applied to a fresh copy of the program.
+pub trait Candidate {
+    /// Sets the program up to try this candidate.
+    fn apply(&self, program: &mut IntCodeProgram);
+}
+
+/// Memory cells to overwrite, as address and value pairs.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Patch(pub Vec<(usize, i64)>);
+
+impl Candidate for Patch {
+    fn apply(&self, program: &mut IntCodeProgram) {
+        for (idx, value) in self.0.iter() {
+            program.set_at(*idx, *value);
+        }
+    }
+}
+
+/// Values to queue as input.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Inputs(pub Vec<i64>);
+
+impl Candidate for Inputs {
+    fn apply(&self, program: &mut IntCodeProgram) {
+        program.in_buf.extend_from_slice(&self.0);
+    }
+}
+
+/// Runs a copy of a program for each candidate, across several threads.
+pub struct Search<'a> {
+    base: &'a IntCodeProgram,
+    threads: usize,
+}
+
+impl<'a> Search<'a> {
+    /// A search from `base`, using `default_threads` threads.
+    pub fn new(base: &'a IntCodeProgram) -> Search<'a> {
+        Search {
+            base,
+            threads: default_threads(),
+        }
+    }
+
+    /// Sets the number of threads; zero is treated as one.
+    pub fn threads(mut self, threads: usize) -> Search<'a> {
+        self.threads = threads;
+        self
+    }
+
+    /// The first candidate whose finished program passes `predicate`.
+    pub fn first<C, I, F>(&self, candidates: I, predicate: F) -> Option<C>
+    where
+        C: Candidate + Send,
+        I: Iterator<Item = C> + Send,
+        F: Fn(&IntCodeProgram) -> bool + Sync,
+    {
+        find_first(candidates, self.threads, |candidate| {
+            predicate(&self.run_candidate(candidate))
+        })
+    }
+
+    /// The candidate whose finished program scores highest, with its score.
+    /// Ties go to the earliest candidate and None scores are skipped.
+    pub fn best<C, I, S, F>(&self, candidates: I, score: F) -> Option<(C, S)>
+    where
+        C: Candidate + Send,
+        I: Iterator<Item = C> + Send,
+        S: Ord + Send,
+        F: Fn(&IntCodeProgram) -> Option<S> + Sync,
+    {
+        find_best(candidates, self.threads, |candidate| {
+            score(&self.run_candidate(candidate))
+        })
+    }
+
+    fn run_candidate<C: Candidate>(&self, candidate: &C) -> IntCodeProgram {
+        let mut program = self.base.clone();
+        candidate.apply(&mut program);
+        program.run();
+        program
+    }
+}
+
+/// One thread per available core, or one if that can't be found out.
+pub fn default_threads() -> usize {
+    thread::available_parallelism().map_or(1, |n| n.get())
+}
+
+/// The first candidate to pass `test`, tried on `threads` threads.
+/// Candidates are handed out in order, so once a match is found every earlier
+/// candidate is already being tested and the lowest-indexed match always wins.
+pub fn find_first<C, I, F>(candidates: I, threads: usize, test: F) -> Option<C>
+where
+    C: Send,
+    I: Iterator<Item = C> + Send,
+    F: Fn(&C) -> bool + Sync,
+{
+    let queue = Mutex::new(candidates.enumerate());
+    let found: Mutex<Option<(usize, C)>> = Mutex::new(None);
+    let done = AtomicBool::new(false);
+
+    thread::scope(|s| {
+        for _ in 0..threads.max(1) {
+            s.spawn(|| {
+                while !done.load(Ordering::Relaxed) {
+                    let next = queue.lock().unwrap().next();
+                    let (idx, candidate) = match next {
+                        Some(next) => next,
+                        None => break,
+                    };
+
+                    if test(&candidate) {
+                        let mut found = found.lock().unwrap();
+                        if found.as_ref().is_none_or(|(i, _)| idx < *i) {
+                            *found = Some((idx, candidate));
+                        }
+                        done.store(true, Ordering::Relaxed);
+                    }
+                }
+            });
+        }
+    });
+
+    found.into_inner().unwrap().map(|(_, candidate)| candidate)
+}
+
+/// The highest scoring candidate and its score, tried on `threads` threads.
+/// Ties go to the earliest candidate.
+pub fn find_best<C, I, S, F>(candidates: I, threads: usize, score: F) -> Option<(C, S)>
+where
+    C: Send,
+    I: Iterator<Item = C> + Send,
+    S: Ord + Send,
+    F: Fn(&C) -> Option<S> + Sync,
+{
+    let queue = Mutex::new(candidates.enumerate());
+    let best: Mutex<Option<(usize, C, S)>> = Mutex::new(None);
+
+    thread::scope(|s| {
+        for _ in 0..threads.max(1) {
+            s.spawn(|| loop {
+                let next = queue.lock().unwrap().next();
+                let (idx, candidate) = match next {
+                    Some(next) => next,
+                    None => break,
+                };
+
+                if let Some(value) = score(&candidate) {
+                    let mut best = best.lock().unwrap();
+                    let better = best
+                        .as_ref()
+                        .is_none_or(|(i, _, v)| value > *v || (value == *v && idx < *i));
+                    if better {
+                        *best = Some((idx, candidate, value));
+                    }
+                }
+            });
+        }
+    });
+
+    best.into_inner()
+        .unwrap()
+        .map(|(_, candidate, value)| (candidate, value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_first() {
+        let program = IntCodeProgram::from_input("1,0,0,0,99");
+        let candidates = (0..5).map(|v| Patch(vec![(1, v), (2, v)]));
+        let found = Search::new(&program)
+            .threads(4)
+            .first(candidates, |p| p.get(0) == 198);
+
+        assert_eq!(found, Some(Patch(vec![(1, 4), (2, 4)])));
+    }
+
+    #[test]
+    fn test_best() {
+        let program = IntCodeProgram::from_input("3,0,3,1,2,0,1,0,4,0,99");
+        let candidates = (-3..3).map(|v| Inputs(vec![v, v - 1]));
+        let best = Search::new(&program).best(candidates, |p| p.out_buf.first().cloned());
+
+        assert_eq!(best, Some((Inputs(vec![-3, -4]), 12)));
+    }
+}
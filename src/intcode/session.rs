@@ -0,0 +1,198 @@
+//! Recording and replaying a program's I/O.
+
+use crate::intcode::word::Word;
+use crate::intcode::IntCodeProgram;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+#[cfg(feature = "std")]
+use std::{fs, io};
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+/// A value passing in or out of a program.
+pub enum Event<W: Word = i64> {
+    /// A value the program read.
+    Input(W),
+    /// A value the program wrote.
+    Output(W),
+}
+
+impl<W: Word> fmt::Display for Event<W> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Event::Input(v) => write!(f, "in {}", v),
+            Event::Output(v) => write!(f, "out {}", v),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+/// A program's I/O in the order it happened. It prints, saves and parses as
+/// one `in N` or `out N` line per event.
+pub struct Session<W: Word = i64> {
+    /// The recorded events, oldest first.
+    pub events: Vec<Event<W>>,
+}
+
+impl<W: Word> Default for Session<W> {
+    fn default() -> Session<W> {
+        Session { events: Vec::new() }
+    }
+}
+
+/// Where a replay first diverged from its session.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Difference<W: Word = i64> {
+    /// The position of the event.
+    pub index: usize,
+    /// The recorded event, or None if the replay went on longer.
+    pub expected: Option<Event<W>>,
+    /// The replayed event, or None if the replay stopped short.
+    pub actual: Option<Event<W>>,
+}
+
+impl<W: Word> fmt::Display for Difference<W> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let describe = |event: &Option<Event<W>>| match event {
+            Some(event) => event.to_string(),
+            None => String::from("nothing"),
+        };
+        write!(
+            f,
+            "event {}: expected {}, got {}",
+            self.index,
+            describe(&self.expected),
+            describe(&self.actual)
+        )
+    }
+}
+
+impl Session {
+    /// Reads a session from its text form, skipping blank lines.
+    pub fn parse(input: &str) -> Result<Session, String> {
+        let mut events = Vec::new();
+        for (i, line) in input.lines().enumerate() {
+            let mut parts = line.split_whitespace();
+            let event = match (parts.next(), parts.next().map(|v| v.parse::<i64>())) {
+                (None, _) => continue,
+                (Some("in"), Some(Ok(v))) => Event::Input(v),
+                (Some("out"), Some(Ok(v))) => Event::Output(v),
+                _ => return Err(format!("Invalid session event on line {}: {}", i + 1, line)),
+            };
+            events.push(event);
+        }
+        Ok(Session { events })
+    }
+
+    #[cfg(feature = "std")]
+    /// Reads a session saved by `save`.
+    pub fn load(filename: &str) -> io::Result<Session> {
+        let contents = fs::read_to_string(filename)?;
+        Session::parse(&contents).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+}
+
+impl<W: Word> Session<W> {
+    /// The values the program read.
+    pub fn inputs(&self) -> Vec<W> {
+        self.events
+            .iter()
+            .filter_map(|event| match event {
+                Event::Input(v) => Some(v.clone()),
+                Event::Output(_) => None,
+            })
+            .collect()
+    }
+
+    /// The values the program wrote.
+    pub fn outputs(&self) -> Vec<W> {
+        self.events
+            .iter()
+            .filter_map(|event| match event {
+                Event::Output(v) => Some(v.clone()),
+                Event::Input(_) => None,
+            })
+            .collect()
+    }
+
+    #[cfg(feature = "std")]
+    /// Writes the session to a file in its text form.
+    pub fn save(&self, filename: &str) -> io::Result<()> {
+        fs::write(filename, self.to_string())
+    }
+
+    /// Feeds the recorded inputs to the program and checks it produces the same
+    /// events, returning the finished program or the first divergence.
+    pub fn replay(
+        &self,
+        mut program: IntCodeProgram<W>,
+    ) -> Result<IntCodeProgram<W>, Difference<W>> {
+        program.record();
+        program.in_buf.extend(self.inputs());
+        program.run();
+        let actual = program.take_session().unwrap_or_default();
+
+        let len = self.events.len().max(actual.events.len());
+        for index in 0..len {
+            let expected = self.events.get(index).cloned();
+            let event = actual.events.get(index).cloned();
+            if expected != event {
+                return Err(Difference {
+                    index,
+                    expected,
+                    actual: event,
+                });
+            }
+        }
+        Ok(program)
+    }
+}
+
+impl<W: Word> fmt::Display for Session<W> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for event in self.events.iter() {
+            writeln!(f, "{}", event)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    const ECHO_SUM: [i64; 13] = [3, 20, 3, 21, 1, 20, 21, 22, 4, 22, 1105, 1, 0];
+
+    #[test]
+    fn test_record_and_replay() {
+        let mut program = IntCodeProgram::from_memory(ECHO_SUM.to_vec());
+        program.record();
+        program.in_buf = vec![1, 2, 3, 4];
+        program.run();
+        let session = program.take_session().unwrap();
+
+        assert_eq!(
+            session.to_string(),
+            "in 1\nin 2\nout 3\nin 3\nin 4\nout 7\n"
+        );
+        assert_eq!(Session::parse(&session.to_string()), Ok(session.clone()));
+        assert!(session
+            .replay(IntCodeProgram::from_memory(ECHO_SUM.to_vec()))
+            .is_ok());
+    }
+
+    #[test]
+    fn test_replay_difference() {
+        let session = Session::parse("in 1\nin 2\nout 3\nin 3\nin 4\nout 8\n").unwrap();
+        let difference = session
+            .replay(IntCodeProgram::from_memory(ECHO_SUM.to_vec()))
+            .unwrap_err();
+
+        assert_eq!(difference.index, 5);
+        assert_eq!(difference.to_string(), "event 5: expected out 8, got out 7");
+    }
+}
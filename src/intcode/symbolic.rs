@@ -0,0 +1,304 @@
+//! Runs simple programs over symbolic values to solve for their inputs.
+
+use crate::intcode::IntCodeProgram;
+use alloc::boxed::Box;
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+use core::fmt;
+
+/// The value of a memory cell in terms of the program's unknown inputs.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Expr {
+    /// A known value.
+    Const(i64),
+    /// The unknown initial value of the cell at this address.
+    Symbol(usize),
+    /// A read from an address that isn't known.
+    Load(Box<Expr>),
+    /// The sum of two expressions.
+    Add(Box<Expr>, Box<Expr>),
+    /// The product of two expressions.
+    Mul(Box<Expr>, Box<Expr>),
+}
+
+impl Expr {
+    // Constants are only folded when the result fits, so an overflowing
+    // program is left unsimplified and has no linear form.
+    fn add(one: Expr, two: Expr) -> Expr {
+        match (one, two) {
+            (Expr::Const(a), Expr::Const(b)) if a.checked_add(b).is_some() => Expr::Const(a + b),
+            (Expr::Const(0), e) | (e, Expr::Const(0)) => e,
+            (a, b) => Expr::Add(Box::new(a), Box::new(b)),
+        }
+    }
+
+    fn mul(one: Expr, two: Expr) -> Expr {
+        match (one, two) {
+            (Expr::Const(a), Expr::Const(b)) if a.checked_mul(b).is_some() => Expr::Const(a * b),
+            (Expr::Const(0), _) | (_, Expr::Const(0)) => Expr::Const(0),
+            (Expr::Const(1), e) | (e, Expr::Const(1)) => e,
+            (a, b) => Expr::Mul(Box::new(a), Box::new(b)),
+        }
+    }
+
+    /// The expression as a sum of symbols times constants, or None if it
+    /// isn't linear or its coefficients overflow.
+    pub fn linear(&self) -> Option<Linear> {
+        match self {
+            Expr::Const(c) => Some(Linear::constant(*c)),
+            Expr::Symbol(s) => {
+                let mut terms = BTreeMap::new();
+                terms.insert(*s, 1);
+                Some(Linear { constant: 0, terms })
+            }
+            Expr::Load(_) => None,
+            Expr::Add(a, b) => a.linear()?.add(&b.linear()?),
+            Expr::Mul(a, b) => {
+                let (a, b) = (a.linear()?, b.linear()?);
+                if a.terms.is_empty() {
+                    b.scale(a.constant)
+                } else if b.terms.is_empty() {
+                    a.scale(b.constant)
+                } else {
+                    None
+                }
+            }
+        }
+    }
+}
+
+impl fmt::Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Expr::Const(c) => write!(f, "{}", c),
+            Expr::Symbol(s) => write!(f, "[{}]", s),
+            Expr::Load(e) => write!(f, "mem[{}]", e),
+            Expr::Add(a, b) => write!(f, "({} + {})", a, b),
+            Expr::Mul(a, b) => write!(f, "({} * {})", a, b),
+        }
+    }
+}
+
+/// A constant plus a sum of symbols times coefficients.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Linear {
+    /// The constant term.
+    pub constant: i64,
+    /// Each symbol's non-zero coefficient.
+    pub terms: BTreeMap<usize, i64>,
+}
+
+impl Linear {
+    fn constant(constant: i64) -> Linear {
+        Linear {
+            constant,
+            terms: BTreeMap::new(),
+        }
+    }
+
+    fn add(&self, other: &Linear) -> Option<Linear> {
+        let mut sum = self.clone();
+        sum.constant = sum.constant.checked_add(other.constant)?;
+        for (symbol, coeff) in other.terms.iter() {
+            let entry = sum.terms.entry(*symbol).or_insert(0);
+            *entry = entry.checked_add(*coeff)?;
+        }
+        sum.terms.retain(|_, coeff| *coeff != 0);
+        Some(sum)
+    }
+
+    fn scale(&self, factor: i64) -> Option<Linear> {
+        let mut scaled = Linear::constant(self.constant.checked_mul(factor)?);
+        if factor != 0 {
+            for (symbol, coeff) in self.terms.iter() {
+                scaled.terms.insert(*symbol, coeff.checked_mul(factor)?);
+            }
+        }
+        Some(scaled)
+    }
+
+    /// The coefficient of `symbol`, zero if it doesn't appear.
+    pub fn coeff(&self, symbol: usize) -> i64 {
+        *self.terms.get(&symbol).unwrap_or(&0)
+    }
+
+    /// The value for the given symbols, or None if it overflows.
+    pub fn eval(&self, values: &BTreeMap<usize, i64>) -> Option<i64> {
+        self.terms
+            .iter()
+            .try_fold(self.constant, |acc, (symbol, coeff)| {
+                acc.checked_add(coeff.checked_mul(*values.get(symbol).unwrap_or(&0))?)
+            })
+    }
+}
+
+impl fmt::Display for Linear {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.constant)?;
+        for (symbol, coeff) in self.terms.iter() {
+            write!(f, " + {}*[{}]", coeff, symbol)?;
+        }
+        Ok(())
+    }
+}
+
+/// Why a program couldn't be run symbolically.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SymbolicError {
+    /// The instruction at this address depends on a symbol.
+    SymbolicOpcode(usize),
+    /// The instruction at this address writes to an address that isn't known.
+    SymbolicAddress(usize),
+    /// An opcode other than add, mul or halt, and the instruction's address.
+    UnsupportedOpcode(i64, usize),
+    /// A parameter mode other than 0, 1 or 2, and the instruction's address.
+    UnknownMode(i64, usize),
+    /// An address outside memory.
+    OutOfBounds(usize),
+}
+
+/// A copy of a program's memory holding expressions instead of values. Only
+/// add, mul and halt are supported, since nothing else can run without
+/// knowing the symbols.
+#[derive(Clone, Debug)]
+pub struct SymbolicProgram {
+    memory: Vec<Expr>,
+    pointer: usize,
+    rel_base: i64,
+}
+
+impl SymbolicProgram {
+    /// Copies the program's state, making each cell in `symbols` an unknown.
+    pub fn new(program: &IntCodeProgram, symbols: &[usize]) -> SymbolicProgram {
+        let mut memory: Vec<Expr> = program.program.iter().map(|v| Expr::Const(*v)).collect();
+        for symbol in symbols.iter() {
+            memory[*symbol] = Expr::Symbol(*symbol);
+        }
+
+        SymbolicProgram {
+            memory,
+            pointer: program.pointer,
+            rel_base: program.rel_base,
+        }
+    }
+
+    /// The expression in the cell at `idx`.
+    pub fn get(&self, idx: usize) -> &Expr {
+        &self.memory[idx]
+    }
+
+    /// Runs until the program halts.
+    pub fn run(&mut self) -> Result<(), SymbolicError> {
+        loop {
+            let instr = match self.fetch(self.pointer)? {
+                Expr::Const(v) => v,
+                _ => return Err(SymbolicError::SymbolicOpcode(self.pointer)),
+            };
+
+            match instr % 100 {
+                1 | 2 => {
+                    let one = self.read(instr / 100 % 10, self.pointer + 1)?;
+                    let two = self.read(instr / 1000 % 10, self.pointer + 2)?;
+                    let three = self.address(instr / 10000 % 10, self.pointer + 3)?;
+                    self.memory[three] = if instr % 100 == 1 {
+                        Expr::add(one, two)
+                    } else {
+                        Expr::mul(one, two)
+                    };
+                    self.pointer += 4;
+                }
+                99 => return Ok(()),
+                opcode => return Err(SymbolicError::UnsupportedOpcode(opcode, self.pointer)),
+            }
+        }
+    }
+
+    fn fetch(&self, idx: usize) -> Result<Expr, SymbolicError> {
+        self.memory
+            .get(idx)
+            .cloned()
+            .ok_or(SymbolicError::OutOfBounds(idx))
+    }
+
+    fn read(&self, mode: i64, idx: usize) -> Result<Expr, SymbolicError> {
+        let param = self.fetch(idx)?;
+        match (mode, param) {
+            (1, param) => Ok(param),
+            (0, Expr::Const(addr)) => self.fetch(addr as usize),
+            (2, Expr::Const(offset)) => self.fetch((self.rel_base + offset) as usize),
+            (0, param) => Ok(Expr::Load(Box::new(param))),
+            (2, param) => Ok(Expr::Load(Box::new(Expr::add(
+                Expr::Const(self.rel_base),
+                param,
+            )))),
+            (mode, _) => Err(SymbolicError::UnknownMode(mode, self.pointer)),
+        }
+    }
+
+    fn address(&self, mode: i64, idx: usize) -> Result<usize, SymbolicError> {
+        let addr = match (mode, self.fetch(idx)?) {
+            (0, Expr::Const(addr)) => addr,
+            (2, Expr::Const(offset)) => self.rel_base + offset,
+            (0, _) | (2, _) => return Err(SymbolicError::SymbolicAddress(self.pointer)),
+            (mode, _) => return Err(SymbolicError::UnknownMode(mode, self.pointer)),
+        };
+
+        if addr < 0 || addr as usize >= self.memory.len() {
+            return Err(SymbolicError::OutOfBounds(idx));
+        }
+        Ok(addr as usize)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::vec;
+
+    #[test]
+    fn test_linear() {
+        let program = IntCodeProgram::from_memory(vec![1, 0, 0, 3, 1, 1, 2, 3, 2, 3, 12, 0, 99]);
+        let mut symbolic = SymbolicProgram::new(&program, &[1, 2]);
+        symbolic.run().unwrap();
+        let linear = symbolic.get(0).linear().unwrap();
+
+        assert_eq!(linear.constant, 0);
+        assert_eq!(linear.coeff(1), 99);
+        assert_eq!(linear.coeff(2), 99);
+    }
+
+    #[test]
+    fn test_non_linear() {
+        let program = IntCodeProgram::from_memory(vec![1, 0, 0, 3, 2, 1, 2, 0, 99]);
+        let mut symbolic = SymbolicProgram::new(&program, &[1, 2]);
+        symbolic.run().unwrap();
+
+        assert_eq!(symbolic.get(0).linear(), None);
+    }
+
+    #[test]
+    fn test_overflow() {
+        let big = i64::MAX / 2 + 1;
+        let program = IntCodeProgram::from_memory(vec![1, 7, 7, 0, 99, 0, 0, big]);
+        let mut symbolic = SymbolicProgram::new(&program, &[]);
+        symbolic.run().unwrap();
+        assert_eq!(symbolic.get(0).linear(), None);
+
+        // Doubles [9] and then multiplies it by [10].
+        for (factor, coeff) in [(3, Some(6)), (big, None)].iter() {
+            let program =
+                IntCodeProgram::from_memory(vec![1, 9, 9, 9, 2, 9, 10, 0, 99, 0, *factor]);
+            let mut symbolic = SymbolicProgram::new(&program, &[9]);
+            symbolic.run().unwrap();
+            assert_eq!(symbolic.get(0).linear().map(|l| l.coeff(9)), *coeff);
+        }
+
+        let mut values = BTreeMap::new();
+        values.insert(1, 2);
+        let linear = Linear::constant(0).add(&Linear::constant(big)).unwrap();
+        assert_eq!(linear.eval(&values), Some(big));
+        let mut doubled = linear.clone();
+        doubled.terms.insert(1, big);
+        assert_eq!(doubled.eval(&values), None);
+    }
+}
@@ -0,0 +1,65 @@
+//! The integer types a program's memory can be made of.
+
+use core::convert::TryFrom;
+use core::fmt;
+
+/// A memory cell. Implementations for fixed-width integers wrap on overflow,
+/// matching the behaviour of the `i64` machine.
+pub trait Word: Clone + Ord + fmt::Debug + fmt::Display + Send + Sync + 'static {
+    /// Converts a small constant, such as a flag or an address, to a word.
+    fn from_i64(value: i64) -> Self;
+
+    /// Converts to `i64`, saturating values that do not fit. Used for
+    /// addresses, jump targets and devices.
+    fn to_i64(&self) -> i64;
+
+    /// The sum of two words.
+    fn add(&self, other: &Self) -> Self;
+
+    /// The product of two words.
+    fn mul(&self, other: &Self) -> Self;
+
+    /// Truncating division by a small divisor, returning the quotient and
+    /// the remainder. Instruction decoding is built on this.
+    fn div_rem(&self, divisor: i64) -> (Self, i64);
+
+    /// Whether the word is zero, as tested by the conditional jumps.
+    fn is_zero(&self) -> bool {
+        *self == Self::from_i64(0)
+    }
+}
+
+macro_rules! primitive_word {
+    ($($t:ty),*) => {
+        $(
+            impl Word for $t {
+                fn from_i64(value: i64) -> $t {
+                    value as $t
+                }
+
+                fn to_i64(&self) -> i64 {
+                    i64::try_from(*self).unwrap_or(if *self < 0 { i64::MIN } else { i64::MAX })
+                }
+
+                fn add(&self, other: &$t) -> $t {
+                    self.wrapping_add(*other)
+                }
+
+                fn mul(&self, other: &$t) -> $t {
+                    self.wrapping_mul(*other)
+                }
+
+                fn div_rem(&self, divisor: i64) -> ($t, i64) {
+                    let divisor = divisor as $t;
+                    (self / divisor, (self % divisor) as i64)
+                }
+
+                fn is_zero(&self) -> bool {
+                    *self == 0
+                }
+            }
+        )*
+    };
+}
+
+primitive_word!(i16, i32, i64, i128);
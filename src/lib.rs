@@ -0,0 +1,18 @@
+//! The pieces shared by the Advent of Code 2019 solutions.
+//!
+//! The [`intcode`] module holds the Intcode virtual machine and its tooling,
+//! while [`grid`] and [`pathfinding`] hold helpers for the puzzles that work
+//! on ASCII maps. The solvers themselves are private to the `aoc19` binary.
+//!
+//! The Intcode core and the map helpers only need `alloc`; everything that
+//! touches the host (text parsing, files and threads) is behind the default
+//! `std` feature.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+#![warn(missing_docs)]
+
+extern crate alloc;
+
+pub mod grid;
+pub mod intcode;
+pub mod pathfinding;
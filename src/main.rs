@@ -1,5 +1,8 @@
 use std::{env, fs};
 
+// Lets the solvers reach the library as `crate::intcode` and so on.
+use aoc19::{grid, intcode, pathfinding};
+
 mod eight;
 mod eighteen;
 mod eleven;
@@ -7,7 +10,6 @@ mod fifteen;
 mod five;
 mod four;
 mod fourteen;
-mod intcode;
 mod nine;
 mod nineteen;
 mod one;
@@ -32,15 +34,19 @@ fn get_input(filename: &str) -> String {
     fs::read_to_string(filename).expect(&err)
 }
 
-fn call_from_str(s: &str) {
+fn call_from_str(s: &str, options: &[String]) {
     match s {
+        "seven" => seven::start(&get_input("inputs/seven"), options),
+        "twentythree" => twentythree::start(&get_input("inputs/twentythree"), options),
+        "twentyfour" => twentyfour::start(&get_input("inputs/twentyfour"), options),
+        "twentyfive" => twentyfive::start(&get_input("inputs/twentyfive"), options),
+        _ if !options.is_empty() => println!("Too many arguments"),
         "one" => one::start(&get_input("inputs/one")),
         "two" => two::start(&get_input("inputs/two")),
         "three" => three::start(&get_input("inputs/three")),
         "four" => four::start(),
         "five" => five::start(&get_input("inputs/five")),
         "six" => six::start(&get_input("inputs/six")),
-        "seven" => seven::start(&get_input("inputs/seven")),
         "eight" => eight::start(&get_input("inputs/eight")),
         "nine" => nine::start(&get_input("inputs/nine")),
         "ten" => ten::start(&get_input("inputs/ten")),
@@ -56,9 +62,6 @@ fn call_from_str(s: &str) {
         "twenty" => twenty::start(&get_input("inputs/twenty")),
         "twentyone" => twentyone::start(&get_input("inputs/twentyone")),
         "twentytwo" => twentytwo::start(&get_input("inputs/twentytwo")),
-        "twentythree" => twentythree::start(&get_input("inputs/twentythree")),
-        "twentyfour" => twentyfour::start(&get_input("inputs/twentyfour")),
-        "twentyfive" => twentyfive::start(&get_input("inputs/twentyfive")),
         _ => println!("No matching function"),
     }
 }
@@ -68,7 +71,6 @@ fn main() {
 
     match args.len() {
         0 | 1 => println!("Too few arguments"),
-        2 => call_from_str(&args[1]),
-        _ => println!("Too many arguments"),
+        _ => call_from_str(&args[1], &args[2..]),
     }
 }
@@ -1,3 +1,190 @@
+use crate::intcode::{IntCodeProgram, IntCodeStatus};
+use std::collections::HashMap;
+
+// Gives up on the square if it hasn't fit by this row, or if this many rows
+// in a row miss the beam.
+const MAX_ROWS: usize = 100_000;
+const MAX_EMPTY_ROWS: usize = 50;
+
+// Answers whether a point is in the beam, deploying a fresh drone program for
+// each point it hasn't been asked about before.
+struct Scanner {
+    drone: IntCodeProgram,
+    cache: HashMap<(usize, usize), bool>,
+    runs: usize,
+}
+
+impl Scanner {
+    fn new(drone: IntCodeProgram) -> Scanner {
+        Scanner {
+            drone,
+            cache: HashMap::new(),
+            runs: 0,
+        }
+    }
+
+    // Fails with the drone's status if it stops without halting or halts
+    // without an answer, rather than treating that as open space.
+    fn pulled(&mut self, x: usize, y: usize) -> Result<bool, IntCodeStatus> {
+        if let Some(pulled) = self.cache.get(&(x, y)) {
+            return Ok(*pulled);
+        }
+        let mut drone = self.drone.clone();
+        drone.in_buf = vec![x as i64, y as i64];
+        drone.run();
+        self.runs += 1;
+
+        let pulled = match (&drone.status, drone.out_buf.first()) {
+            (IntCodeStatus::Halted, Some(output)) => *output == 1,
+            _ => return Err(drone.status),
+        };
+        self.cache.insert((x, y), pulled);
+        Ok(pulled)
+    }
+}
+
 pub fn start(input: &str) {
-    println!("{}", input);
+    let mut scanner = Scanner::new(IntCodeProgram::from_input(input));
+    match affected(&mut scanner, 50) {
+        Ok(points) => println!("Points affected: {}", points),
+        Err(status) => return println!("The drone failed: {:?}", status),
+    }
+
+    match closest_square(&mut scanner, 100) {
+        Ok(Some((x, y))) => println!("Closest square: {}", x * 10000 + y),
+        Ok(None) => println!("No square fits in the beam the drone found"),
+        Err(status) => println!("The drone failed: {:?}", status),
+    }
+    println!("Drone runs: {}", scanner.runs);
+}
+
+fn affected(scanner: &mut Scanner, size: usize) -> Result<usize, IntCodeStatus> {
+    let mut count = 0;
+    for y in 0..size {
+        for x in 0..size {
+            if scanner.pulled(x, y)? {
+                count += 1;
+            }
+        }
+    }
+    Ok(count)
+}
+
+// Walks down the beam one row at a time, following its left and right edges.
+// Both only ever move right, so each row starts from the previous one's edges.
+// A square whose bottom-left corner is on the current row's left edge fits
+// once the row `size - 1` above reaches far enough right. Each row that misses
+// the beam widens the next row's scan, up to `MAX_EMPTY_ROWS` of them.
+fn closest_square(
+    scanner: &mut Scanner,
+    size: usize,
+) -> Result<Option<(usize, usize)>, IntCodeStatus> {
+    let mut edges: Vec<Option<(usize, usize)>> = Vec::new();
+    let (mut left, mut right) = (0, 0);
+    let mut empty_rows = 0;
+
+    for y in 0..MAX_ROWS {
+        // Rows near the emitter can miss the beam entirely.
+        let limit = left.max(right) + 10 * (empty_rows + 1);
+        let mut row = None;
+        for start in left..=limit {
+            if scanner.pulled(start, y)? {
+                let mut end = right.max(start);
+                while scanner.pulled(end + 1, y)? {
+                    end += 1;
+                }
+                row = Some((start, end));
+                break;
+            }
+        }
+        edges.push(row);
+
+        let (start, end) = match row {
+            Some(row) => row,
+            None if empty_rows + 1 < MAX_EMPTY_ROWS => {
+                empty_rows += 1;
+                continue;
+            }
+            None => return Ok(None),
+        };
+        empty_rows = 0;
+        left = start;
+        right = end;
+
+        if y + 1 >= size {
+            let top = y + 1 - size;
+            if let Some((_, top_end)) = edges[top] {
+                if top_end >= left + size - 1 {
+                    return Ok(Some((left, top)));
+                }
+            }
+        }
+    }
+    Ok(None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intcode::compiler::compile;
+
+    // A beam from the origin spreading between the slopes 20/17 and 20/13.
+    const DRONE: &str = "fn main() {
+                             let x = input();
+                             let y = input();
+                             let pulled = 0;
+                             if (x * 20 >= y * 13) { if (x * 20 <= y * 17) { pulled = 1; } }
+                             output(pulled);
+                         }";
+
+    fn beam(x: usize, y: usize) -> bool {
+        x * 20 >= y * 13 && x * 20 <= y * 17
+    }
+
+    fn scanner() -> Scanner {
+        Scanner::new(IntCodeProgram::from_memory(compile(DRONE).unwrap()))
+    }
+
+    #[test]
+    fn test_one() {
+        let mut scanner = scanner();
+        let expected = (0..50)
+            .flat_map(|y| (0..50).map(move |x| (x, y)))
+            .filter(|(x, y)| beam(*x, *y))
+            .count();
+
+        assert_eq!(affected(&mut scanner, 50), Ok(expected));
+        assert_eq!(scanner.runs, 2500);
+        affected(&mut scanner, 50).unwrap();
+        assert_eq!(scanner.runs, 2500);
+
+        // A drone that wants a third coordinate, and one that faults.
+        let mut waiting = Scanner::new(IntCodeProgram::from_input("3,0,3,0,3,0,99"));
+        assert_eq!(waiting.pulled(0, 0), Err(IntCodeStatus::AwaitingInput));
+        let mut broken = Scanner::new(IntCodeProgram::from_input("3,0,3,0,98"));
+        assert!(matches!(
+            closest_square(&mut broken, 10),
+            Err(IntCodeStatus::Error(_))
+        ));
+        assert_eq!(broken.runs, 1);
+
+        // A drone that never reports the beam is given up on quickly.
+        let mut blind = Scanner::new(IntCodeProgram::from_input("3,0,3,0,104,0,99"));
+        assert_eq!(closest_square(&mut blind, 10), Ok(None));
+        assert!(blind.runs < 15_000);
+    }
+
+    #[test]
+    fn test_two() {
+        let size = 10;
+        let fits = |x: usize, y: usize| (y..y + size).all(|y| (x..x + size).all(|x| beam(x, y)));
+        let expected = (0..200)
+            .flat_map(|y| (0..200).map(move |x| (x, y)))
+            .find(|(x, y)| fits(*x, *y))
+            .unwrap();
+
+        let mut scanner = scanner();
+        assert_eq!(closest_square(&mut scanner, size), Ok(Some(expected)));
+        assert!(scanner.runs < 2000);
+    }
 }
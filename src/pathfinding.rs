@@ -0,0 +1,72 @@
+//! Graph searches shared by the maze puzzles.
+
+use alloc::collections::{BTreeMap, BinaryHeap, VecDeque};
+use alloc::vec;
+use alloc::vec::Vec;
+use core::cmp::Reverse;
+
+/// Finds a path with the fewest steps from `start` to a state accepted by
+/// `goal`, returning every state along it with both ends included.
+pub fn bfs<S, N, I, G>(start: S, mut neighbours: N, mut goal: G) -> Option<Vec<S>>
+where
+    S: Clone + Ord,
+    N: FnMut(&S) -> I,
+    I: IntoIterator<Item = S>,
+    G: FnMut(&S) -> bool,
+{
+    let mut previous = BTreeMap::new();
+    previous.insert(start.clone(), None);
+    let mut queue = VecDeque::new();
+    queue.push_back(start);
+
+    while let Some(state) = queue.pop_front() {
+        if goal(&state) {
+            let mut path = vec![state];
+            while let Some(Some(before)) = previous.get(path.last().unwrap()) {
+                path.push(S::clone(before));
+            }
+            path.reverse();
+            return Some(path);
+        }
+        for next in neighbours(&state) {
+            if !previous.contains_key(&next) {
+                previous.insert(next.clone(), Some(state.clone()));
+                queue.push_back(next);
+            }
+        }
+    }
+    None
+}
+
+/// Finds the cheapest path from `start` to a state accepted by `goal`, where
+/// `neighbours` yields each next state with the cost of moving to it.
+/// Returns the goal state reached and the total cost.
+pub fn dijkstra<S, N, I, G>(start: S, mut neighbours: N, mut goal: G) -> Option<(S, usize)>
+where
+    S: Clone + Ord,
+    N: FnMut(&S) -> I,
+    I: IntoIterator<Item = (S, usize)>,
+    G: FnMut(&S) -> bool,
+{
+    let mut best = BTreeMap::new();
+    let mut queue = BinaryHeap::new();
+    best.insert(start.clone(), 0);
+    queue.push(Reverse((0, start)));
+
+    while let Some(Reverse((cost, state))) = queue.pop() {
+        if best.get(&state).is_some_and(|b| *b < cost) {
+            continue;
+        }
+        if goal(&state) {
+            return Some((state, cost));
+        }
+        for (next, step) in neighbours(&state) {
+            let cost = cost + step;
+            if best.get(&next).is_none_or(|b| cost < *b) {
+                best.insert(next.clone(), cost);
+                queue.push(Reverse((cost, next)));
+            }
+        }
+    }
+    None
+}
@@ -1,87 +1,258 @@
+use crate::intcode::executor::{channel, run, Executor};
+use crate::intcode::search::{default_threads, find_best};
 use crate::intcode::{IntCodeProgram, IntCodeStatus};
 
-pub fn start(input: &str) {
-    let mut permutations: Vec<Vec<u32>> = Vec::new();
-    heaps_algo(5, &mut [0, 1, 2, 3, 4], &mut permutations);
-    run_without_feedback(input, permutations);
+const DEFAULT_AMPS: usize = 5;
+// Each phase setting is a full run of every amplifier, so refuse searches
+// much bigger than ten amplifiers over ten phases.
+const MAX_SETTINGS: usize = 10_000_000;
 
-    let mut permutations: Vec<Vec<u32>> = Vec::new();
-    heaps_algo(5, &mut [5, 6, 7, 8, 9], &mut permutations);
-    run_with_feedback(input, permutations);
+struct Options {
+    amps: usize,
+    phases: Vec<u32>,
+    feedback_phases: Vec<u32>,
+    threads: usize,
 }
 
-fn run_without_feedback(input: &str, permutations: Vec<Vec<u32>>) {
-    let mut max_output_signal = 0;
-    for phase_setting in permutations.iter() {
-        let mut output_signal = 0;
-        for amp_phase in phase_setting.iter() {
-            let mut amp = IntCodeProgram::from_input(&input);
-            amp.in_buf.push(*amp_phase as i64);
-            amp.in_buf.push(output_signal);
-            amp.run();
-            output_signal = amp.out_buf.remove(0);
+impl Options {
+    // Phase sets default to the puzzle's, 0 up to the amplifier count and
+    // the same number after that for the feedback loop.
+    fn parse(args: &[String]) -> Result<Options, String> {
+        let mut amps = DEFAULT_AMPS;
+        let mut phases = None;
+        let mut feedback_phases = None;
+        let mut threads = default_threads();
+
+        let mut args = args.iter();
+        while let Some(flag) = args.next() {
+            let value = args.next().ok_or(format!("Missing value for {}", flag))?;
+            match flag.as_str() {
+                "--amps" => amps = parse_number(value)?,
+                "--phases" => phases = Some(parse_phases(value)?),
+                "--feedback-phases" => feedback_phases = Some(parse_phases(value)?),
+                "--threads" => threads = parse_number(value)?,
+                _ => return Err(format!("Unknown option: {}", flag)),
+            }
+        }
+
+        // Even the fewest phases give `amps!` settings, so check that before
+        // building the default phase sets.
+        if amps == 0 {
+            return Err(String::from("At least one amplifier is needed"));
+        }
+        if settings(amps, amps).is_none() {
+            return Err(format!("Too many phase settings for {} amplifiers", amps));
+        }
+
+        let options = Options {
+            amps,
+            phases: phases.unwrap_or_else(|| (0..amps as u32).collect()),
+            feedback_phases: feedback_phases
+                .unwrap_or_else(|| (amps as u32..2 * amps as u32).collect()),
+            threads,
+        };
+        for set in [&options.phases, &options.feedback_phases].iter() {
+            if set.len() < amps {
+                return Err(format!("{} amplifiers need at least {} phases", amps, amps));
+            }
+            if settings(set.len(), amps).is_none() {
+                return Err(format!(
+                    "Too many phase settings for {} amplifiers over {} phases",
+                    amps,
+                    set.len()
+                ));
+            }
+        }
+        Ok(options)
+    }
+}
+
+// The number of ways to assign `amps` of `phases` distinct phases, if it's
+// within `MAX_SETTINGS`.
+fn settings(phases: usize, amps: usize) -> Option<usize> {
+    (phases - amps + 1..=phases)
+        .try_fold(1usize, |count, n| count.checked_mul(n))
+        .filter(|count| *count <= MAX_SETTINGS)
+}
+
+fn parse_number<T: std::str::FromStr>(value: &str) -> Result<T, String> {
+    value
+        .parse()
+        .map_err(|_| format!("Invalid number: {}", value))
+}
+
+// Either a range like `5-9` or a list like `0,2,4`. A range with more phases
+// than `MAX_SETTINGS` could never be searched, so it's refused unbuilt.
+fn parse_phases(value: &str) -> Result<Vec<u32>, String> {
+    let mut phases: Vec<u32> = match value.split_once('-') {
+        Some((lo, hi)) => {
+            let (lo, hi): (u32, u32) = (parse_number(lo)?, parse_number(hi)?);
+            if hi.saturating_sub(lo) as usize >= MAX_SETTINGS {
+                return Err(format!("Too many phases in {}", value));
+            }
+            (lo..=hi).collect()
+        }
+        None => value
+            .split(',')
+            .map(parse_number)
+            .collect::<Result<_, _>>()?,
+    };
+    phases.sort_unstable();
+    phases.dedup();
+    Ok(phases)
+}
+
+pub fn start(input: &str, args: &[String]) {
+    let options = match Options::parse(args) {
+        Ok(options) => options,
+        Err(e) => {
+            println!("{}", e);
+            return println!(
+                "Usage: seven [--amps N] [--phases LO-HI|A,B,..] \
+                 [--feedback-phases LO-HI|A,B,..] [--threads N]"
+            );
         }
-        if output_signal > max_output_signal {
-            max_output_signal = output_signal;
+    };
+
+    let best = find_best(
+        Permutations::new(options.phases.clone(), options.amps),
+        options.threads,
+        |phase_setting| chain(input, phase_setting),
+    );
+    report(best);
+
+    let best = find_best(
+        Permutations::new(options.feedback_phases.clone(), options.amps),
+        options.threads,
+        |phase_setting| feedback(input, phase_setting),
+    );
+    report(best);
+}
+
+fn report(best: Option<(Vec<u32>, i64)>) {
+    match best {
+        Some((phase_setting, max_output_signal)) => {
+            let phases: Vec<String> = phase_setting.iter().map(u32::to_string).collect();
+            println!(
+                "Max output signal: {} (phase setting {})",
+                max_output_signal,
+                phases.join(",")
+            );
         }
+        None => println!("No phase setting produced a signal"),
     }
+}
 
-    println!("Max output signal: {}", max_output_signal);
+// Lazily yields every ordered choice of `k` distinct items, in the order
+// their positions would sort, without building them all up front.
+struct Permutations<T> {
+    items: Vec<T>,
+    k: usize,
+    indices: Vec<usize>,
+    // How many more swaps each of the first `k` positions has left before it
+    // wraps round.
+    cycles: Vec<usize>,
+    started: bool,
+    done: bool,
 }
 
-fn run_with_feedback(input: &str, permutations: Vec<Vec<u32>>) {
-    let mut max_output_signal = 0;
-    for phase_setting in permutations.iter() {
-        let output_signal = feedback(input, &phase_setting);
-        if output_signal > max_output_signal {
-            max_output_signal = output_signal;
+impl<T: Clone> Permutations<T> {
+    fn new(items: Vec<T>, k: usize) -> Permutations<T> {
+        let n = items.len();
+        Permutations {
+            k,
+            indices: (0..n).collect(),
+            cycles: (0..k.min(n)).map(|i| n - i).collect(),
+            done: k > n,
+            started: false,
+            items,
         }
     }
 
-    println!("Max output signal: {}", max_output_signal);
+    fn current(&self) -> Vec<T> {
+        self.indices[..self.k]
+            .iter()
+            .map(|i| self.items[*i].clone())
+            .collect()
+    }
 }
 
-fn heaps_algo(k: u8, a: &mut [u32], v: &mut Vec<Vec<u32>>) {
-    if k == 1 {
-        let mut permutation = Vec::new();
-        permutation.extend_from_slice(a);
-        v.push(permutation);
-    } else {
-        heaps_algo(k - 1, a, v);
-        for i in 0..k - 1 {
-            if k % 2 == 0 {
-                a.swap(i as usize, (k - 1) as usize);
+impl<T: Clone> Iterator for Permutations<T> {
+    type Item = Vec<T>;
+
+    fn next(&mut self) -> Option<Vec<T>> {
+        if self.done {
+            return None;
+        }
+        if !self.started {
+            self.started = true;
+            return Some(self.current());
+        }
+
+        let n = self.indices.len();
+        for i in (0..self.k).rev() {
+            self.cycles[i] -= 1;
+            if self.cycles[i] == 0 {
+                self.indices[i..].rotate_left(1);
+                self.cycles[i] = n - i;
             } else {
-                a.swap(0, (k - 1) as usize);
+                let j = n - self.cycles[i];
+                self.indices.swap(i, j);
+                return Some(self.current());
             }
-            heaps_algo(k - 1, a, v)
         }
+        self.done = true;
+        None
+    }
+}
+
+// Returns None if an amplifier doesn't halt with a signal for the next one.
+fn chain(input: &str, phase_setting: &[u32]) -> Option<i64> {
+    let mut output_signal = 0;
+    for amp_phase in phase_setting.iter() {
+        let mut amp = IntCodeProgram::from_input(input);
+        amp.in_buf.push(*amp_phase as i64);
+        amp.in_buf.push(output_signal);
+        amp.run();
+        if amp.status != IntCodeStatus::Halted {
+            return None;
+        }
+        output_signal = *amp.out_buf.first()?;
     }
+    Some(output_signal)
 }
 
-fn feedback(input: &str, phase_setting: &Vec<u32>) -> i64 {
-    let mut amplifiers: Vec<IntCodeProgram> = Vec::new();
+// Returns None if the loop deadlocks or an amplifier stops for any reason
+// other than halting.
+fn feedback(input: &str, phase_setting: &[u32]) -> Option<i64> {
+    let mut executor = Executor::new();
+    let (first, mut output) = channel();
+    let mut amps = Vec::new();
     for amp_phase in phase_setting.iter() {
-        let mut amp = IntCodeProgram::from_input(&input);
+        let mut amp = IntCodeProgram::from_input(input);
         amp.in_buf.push(*amp_phase as i64);
-        amplifiers.push(amp);
+        let (tx, rx) = channel();
+        amps.push(executor.spawn(run(amp, output, tx)));
+        output = rx;
     }
 
-    let mut output_signal: i64 = 0;
-    loop {
-        for amp in amplifiers.iter_mut() {
-            amp.in_buf.push(output_signal);
-            amp.status = IntCodeStatus::Ready;
-            amp.run();
-            output_signal = amp.out_buf.remove(0);
+    first.send(0);
+    let last_signal = executor.spawn(async move {
+        let mut output_signal = None;
+        while let Some(signal) = output.recv().await {
+            output_signal = Some(signal);
+            first.send(signal);
         }
+        output_signal
+    });
 
-        if amplifiers.last().unwrap().status == IntCodeStatus::Halted {
-            break;
+    executor.run();
+    for amp in amps.iter() {
+        if amp.take()?.status != IntCodeStatus::Halted {
+            return None;
         }
     }
-
-    return output_signal;
+    last_signal.take()?
 }
 
 #[cfg(test)]
@@ -94,6 +265,82 @@ mod tests {
                      26,27,4,27,1001,28,-1,28,1005,28,6,99,0,0,5";
         let phase_setting = vec![9, 8, 7, 6, 5];
 
-        assert_eq!(feedback(input, &phase_setting), 139629729);
+        assert_eq!(feedback(input, &phase_setting), Some(139629729));
+
+        // Halts without ever sending a signal.
+        assert_eq!(feedback("3,0,99", &phase_setting), None);
+        // Wants two signals before sending one, so the loop deadlocks.
+        assert_eq!(feedback("3,0,3,0,3,0,4,0,99", &phase_setting), None);
+    }
+
+    #[test]
+    fn test_chain_one() {
+        let input = "3,15,3,16,1002,16,10,16,1,16,15,15,4,15,99,0,0";
+        let permutations = Permutations::new(vec![0, 1, 2, 3, 4], 5);
+        let best = find_best(permutations, 4, |p| chain(input, p));
+
+        assert_eq!(best, Some((vec![4, 3, 2, 1, 0], 43210)));
+
+        assert_eq!(chain("99", &[0, 1]), None);
+        assert_eq!(chain("3,0,3,0,4,0,98", &[0, 1]), None);
+    }
+
+    #[test]
+    fn test_permutations() {
+        let all: Vec<Vec<u32>> = Permutations::new(vec![0, 1, 2], 3).collect();
+        assert_eq!(
+            all,
+            vec![
+                vec![0, 1, 2],
+                vec![0, 2, 1],
+                vec![1, 0, 2],
+                vec![1, 2, 0],
+                vec![2, 0, 1],
+                vec![2, 1, 0],
+            ]
+        );
+
+        let mut five: Vec<Vec<u32>> = Permutations::new((0..5).collect(), 5).collect();
+        assert_eq!(five.len(), 120);
+        five.sort();
+        five.dedup();
+        assert_eq!(five.len(), 120);
+
+        let pairs: Vec<Vec<u32>> = Permutations::new(vec![5, 6, 7, 8], 2).collect();
+        assert_eq!(pairs.len(), 12);
+        assert!(pairs.iter().all(|p| p.len() == 2 && p[0] != p[1]));
+
+        assert_eq!(Permutations::new(vec![1, 2], 3).count(), 0);
+        assert_eq!(
+            Permutations::new(vec![1, 2], 0).collect::<Vec<_>>(),
+            vec![Vec::<i32>::new()]
+        );
+    }
+
+    #[test]
+    fn test_options() {
+        let args = |s: &str| s.split_whitespace().map(String::from).collect::<Vec<_>>();
+
+        let options = Options::parse(&[]).unwrap();
+        assert_eq!(options.amps, 5);
+        assert_eq!(options.phases, vec![0, 1, 2, 3, 4]);
+        assert_eq!(options.feedback_phases, vec![5, 6, 7, 8, 9]);
+
+        let options = Options::parse(&args("--amps 3 --phases 4,0,2,2 --threads 2")).unwrap();
+        assert_eq!(options.phases, vec![0, 2, 4]);
+        assert_eq!(options.feedback_phases, vec![3, 4, 5]);
+        assert_eq!(options.threads, 2);
+
+        assert!(Options::parse(&args("--amps 3 --phases 0-1")).is_err());
+        assert!(Options::parse(&args("--amps")).is_err());
+        assert!(Options::parse(&args("--amps 0")).is_err());
+        assert!(Options::parse(&args("--amps 10")).is_ok());
+        assert!(Options::parse(&args("--amps 12")).is_err());
+        assert!(Options::parse(&args("--amps 5 --phases 0-40")).is_err());
+        assert!(Options::parse(&args("--amps 3000000000 --phases 0,1")).is_err());
+        assert!(Options::parse(&args("--amps 1 --phases 0-4000000000")).is_err());
+        assert!(Options::parse(&args("--amps 1 --phases 9-0")).is_err());
+        assert!(Options::parse(&args("--speed 3")).is_err());
+        assert!(Options::parse(&args("--phases 0-x")).is_err());
     }
 }
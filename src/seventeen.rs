@@ -1,3 +1,349 @@
+use crate::grid::{Direction, Grid, Pos};
+use crate::intcode::{IntCodeProgram, IntCodeStatus};
+use std::fmt;
+
+// Movement functions and the main routine are each limited to 20 characters,
+// not counting the newline.
+const MAX_LINE: usize = 20;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Turn {
+    Left,
+    Right,
+}
+
+// One turn followed by a run of forward steps, e.g. `R,8`. Only the first
+// move can go without a turn, when the robot already faces along the scaffold.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+struct Move {
+    turn: Option<Turn>,
+    steps: usize,
+}
+
+impl fmt::Display for Move {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.turn {
+            Some(Turn::Left) => write!(f, "L,")?,
+            Some(Turn::Right) => write!(f, "R,")?,
+            None => {}
+        }
+        write!(f, "{}", self.steps)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Routine {
+    main: Vec<usize>,
+    functions: Vec<Vec<Move>>,
+}
+
+impl Routine {
+    // The robot expects the main routine, then functions A, B and C, then
+    // whether to show the video feed, one per line.
+    fn to_input(&self) -> Vec<i64> {
+        let mut lines = vec![self
+            .main
+            .iter()
+            .map(|f| ((b'A' + *f as u8) as char).to_string())
+            .collect::<Vec<_>>()
+            .join(",")];
+        for i in 0..3 {
+            let function = self.functions.get(i).map_or(&[][..], |f| &f[..]);
+            lines.push(join(function));
+        }
+        lines.push(String::from("n"));
+
+        lines
+            .iter()
+            .flat_map(|line| line.bytes().chain(Some(b'\n')))
+            .map(i64::from)
+            .collect()
+    }
+}
+
 pub fn start(input: &str) {
-    println!("{}", input);
+    let mut program = IntCodeProgram::from_input(input);
+    let grid = match camera_view(&mut program) {
+        Ok(grid) => grid,
+        Err(status) => return println!("The camera produced no map: {:?}", status),
+    };
+    println!("Alignment parameters: {}", alignment_sum(&grid));
+    if robot(&grid).is_none() {
+        return println!("The camera view shows no robot");
+    }
+
+    let path = match trace_path(&grid) {
+        Some(path) if path.is_empty() => {
+            return println!("The robot can't move along the scaffold")
+        }
+        Some(path) => path,
+        None => return println!("The scaffold loops back on itself"),
+    };
+    println!("Path: {}", join(&path));
+    let routine = match compress(&path) {
+        Some(routine) => routine,
+        None => return println!("The path does not fit in three movement functions"),
+    };
+
+    let mut program = IntCodeProgram::from_input(input);
+    program.set_at(0, 2);
+    program.in_buf = routine.to_input();
+    program.run();
+    match program.out_buf.last() {
+        Some(dust) if *dust > 127 => println!("Dust collected: {}", dust),
+        _ => println!("{}", ascii(&program.out_buf)),
+    }
+}
+
+// Fails with the program's status if it doesn't halt after drawing a map.
+fn camera_view(program: &mut IntCodeProgram) -> Result<Grid, IntCodeStatus> {
+    program.run();
+    let view = ascii(&program.out_buf);
+    program.out_buf.clear();
+    let grid = Grid::parse(&view);
+    if program.status != IntCodeStatus::Halted || grid.height() == 0 {
+        return Err(program.status.clone());
+    }
+    Ok(grid)
+}
+
+fn ascii(output: &[i64]) -> String {
+    output
+        .iter()
+        .filter(|c| (0..128).contains(*c))
+        .map(|c| *c as u8 as char)
+        .collect()
+}
+
+fn is_scaffold(c: u8) -> bool {
+    matches!(c, b'#' | b'^' | b'v' | b'<' | b'>')
+}
+
+fn alignment_sum(grid: &Grid) -> usize {
+    grid.positions(is_scaffold)
+        .filter(|pos| {
+            let around = grid.neighbours(*pos);
+            around
+                .filter(|n| grid.get(*n).is_some_and(is_scaffold))
+                .count()
+                == 4
+        })
+        .map(|(x, y)| x * y)
+        .sum()
+}
+
+fn robot(grid: &Grid) -> Option<(Pos, Direction)> {
+    let pos = grid
+        .positions(|c| matches!(c, b'^' | b'v' | b'<' | b'>'))
+        .next()?;
+    let dir = match grid.get(pos)? {
+        b'^' => Direction::Up,
+        b'>' => Direction::Right,
+        b'v' => Direction::Down,
+        _ => Direction::Left,
+    };
+    Some((pos, dir))
+}
+
+// Follows the scaffold from the robot, going straight over intersections and
+// turning only at corners, until it reaches the far end. The robot sets off
+// without turning if it already faces along the scaffold.
+//
+// Without a loop each tile is crossed at most twice, once each way through an
+// intersection, so a longer walk means the scaffold loops and None is
+// returned.
+fn trace_path(grid: &Grid) -> Option<Vec<Move>> {
+    let (mut pos, mut dir) = match robot(grid) {
+        Some(robot) => robot,
+        None => return Some(Vec::new()),
+    };
+    let mut walk_left = 2 * grid.positions(is_scaffold).count();
+    let open = |pos: Pos, dir: Direction| {
+        grid.step(pos, dir)
+            .filter(|next| grid.get(*next).is_some_and(is_scaffold))
+    };
+
+    let mut path = Vec::new();
+    let mut turn = None;
+    loop {
+        let mut steps = 0;
+        while let Some(next) = open(pos, dir) {
+            walk_left = walk_left.checked_sub(1)?;
+            pos = next;
+            steps += 1;
+        }
+        if steps > 0 {
+            path.push(Move { turn, steps });
+        }
+
+        turn = if open(pos, dir.turn_left()).is_some() {
+            Some(Turn::Left)
+        } else if open(pos, dir.turn_right()).is_some() {
+            Some(Turn::Right)
+        } else {
+            return Some(path);
+        };
+        dir = match turn {
+            Some(Turn::Left) => dir.turn_left(),
+            _ => dir.turn_right(),
+        };
+    }
+}
+
+fn join(moves: &[Move]) -> String {
+    moves
+        .iter()
+        .map(|m| m.to_string())
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+fn compress(path: &[Move]) -> Option<Routine> {
+    let mut routine = Routine {
+        main: Vec::new(),
+        functions: Vec::new(),
+    };
+    if fit(path, &mut routine) {
+        return Some(routine);
+    }
+    None
+}
+
+// Covers the rest of the path with existing functions where they match,
+// otherwise defines a new one from a prefix of it, backtracking on failure.
+fn fit(path: &[Move], routine: &mut Routine) -> bool {
+    if path.is_empty() {
+        return true;
+    }
+    if routine.main.len() * 2 + 1 > MAX_LINE {
+        return false;
+    }
+
+    for f in 0..routine.functions.len() {
+        if path.starts_with(&routine.functions[f]) {
+            routine.main.push(f);
+            if fit(&path[routine.functions[f].len()..], routine) {
+                return true;
+            }
+            routine.main.pop();
+        }
+    }
+
+    if routine.functions.len() < 3 {
+        let f = routine.functions.len();
+        for len in (1..=path.len()).rev() {
+            if join(&path[..len]).len() > MAX_LINE {
+                continue;
+            }
+            routine.functions.push(path[..len].to_vec());
+            routine.main.push(f);
+            if fit(&path[len..], routine) {
+                return true;
+            }
+            routine.main.pop();
+            routine.functions.pop();
+        }
+    }
+    false
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Prints the zero-terminated ASCII text stored after the code.
+    fn camera_program(view: &str) -> IntCodeProgram {
+        let mut memory = vec![109, 10, 204, 0, 109, 1, 1205, 0, 2, 99];
+        memory.extend(view.bytes().map(i64::from));
+        memory.push(0);
+        IntCodeProgram::from_memory(memory)
+    }
+
+    fn expand(routine: &Routine) -> Vec<Move> {
+        routine
+            .main
+            .iter()
+            .flat_map(|f| routine.functions[*f].iter().cloned())
+            .collect()
+    }
+
+    #[test]
+    fn test_one() {
+        let view = "..#..........\n\
+                    ..#..........\n\
+                    #######...###\n\
+                    #.#...#...#.#\n\
+                    #############\n\
+                    ..#...#...#..\n\
+                    ..#####...^..\n\n";
+        let grid = camera_view(&mut camera_program(view)).unwrap();
+
+        assert_eq!(grid.height(), 7);
+        assert_eq!(alignment_sum(&grid), 76);
+
+        let mut empty = IntCodeProgram::from_memory(Vec::new());
+        assert!(matches!(
+            camera_view(&mut empty),
+            Err(IntCodeStatus::Error(_))
+        ));
+        assert_eq!(
+            camera_view(&mut IntCodeProgram::from_input("99")),
+            Err(IntCodeStatus::Halted)
+        );
+    }
+
+    #[test]
+    fn test_two() {
+        let grid = Grid::parse(
+            "#######...#####\n\
+             #.....#...#...#\n\
+             #.....#...#...#\n\
+             ......#...#...#\n\
+             ......#...###.#\n\
+             ......#.....#.#\n\
+             ^########...#.#\n\
+             ......#.#...#.#\n\
+             ......#########\n\
+             ........#...#..\n\
+             ....#########..\n\
+             ....#...#......\n\
+             ....#...#......\n\
+             ....#...#......\n\
+             ....#####......",
+        );
+        let path = trace_path(&grid).unwrap();
+        assert_eq!(
+            join(&path),
+            "R,8,R,8,R,4,R,4,R,8,L,6,L,2,R,4,R,4,R,8,R,8,R,8,L,6,L,2"
+        );
+
+        let routine = compress(&path).unwrap();
+        assert_eq!(expand(&routine), path);
+        assert!(routine.functions.len() <= 3);
+
+        let input = ascii(&routine.to_input());
+        let lines: Vec<&str> = input.lines().collect();
+        assert_eq!(lines.len(), 5);
+        assert!(lines.iter().all(|line| line.len() <= MAX_LINE));
+        assert_eq!(lines[4], "n");
+
+        // Facing along the scaffold, the robot sets off without turning.
+        let grid = Grid::parse(
+            "..#####
+..#...#
+..^...#
+......#",
+        );
+        let path = trace_path(&grid).unwrap();
+        assert_eq!(join(&path), "2,R,4,R,3");
+        let routine = compress(&path).unwrap();
+        assert_eq!(expand(&routine), path);
+
+        // Round and round a closed loop, or nowhere when facing away from a
+        // dead end.
+        assert_eq!(trace_path(&Grid::parse("^###\n#..#\n####")), None);
+        assert_eq!(trace_path(&Grid::parse("^\n#")), Some(Vec::new()));
+        let path = trace_path(&Grid::parse("v\n#")).unwrap();
+        assert_eq!(join(&path), "1");
+    }
 }
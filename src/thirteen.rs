@@ -19,6 +19,13 @@ fn run_game(program: &mut IntCodeProgram) -> Screen {
 
     while program.status != IntCodeStatus::Halted {
         program.run();
+        match program.status {
+            IntCodeStatus::AwaitingInput | IntCodeStatus::Halted => {}
+            ref status => {
+                println!("The game stopped: {:?}", status);
+                break;
+            }
+        }
         screen = parse_output(&program.out_buf);
         program.in_buf.push(joystick(&screen));
         program.out_buf = Vec::new();
@@ -1,3 +1,304 @@
+use crate::grid::{Direction, Grid, Pos};
+use crate::pathfinding::bfs;
+use std::collections::HashMap;
+
+// How far down the recursive maze the search may go before giving up.
+const MAX_DEPTH: usize = 200;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Side {
+    Outer,
+    Inner,
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Mode {
+    Flat,
+    Recursive { max_depth: usize },
+}
+
+#[derive(Clone, Debug)]
+struct Portal {
+    label: String,
+    side: Side,
+}
+
+// A position in the maze and how many levels down it is.
+type State = (Pos, usize);
+
+struct Maze {
+    grid: Grid,
+    // Keyed by the open tile next to each label.
+    portals: HashMap<Pos, Portal>,
+    links: HashMap<Pos, Pos>,
+    start: Pos,
+    end: Pos,
+}
+
+impl Maze {
+    fn parse(input: &str) -> Result<Maze, String> {
+        let grid = Grid::parse(input);
+
+        // Outer labels sit just outside the bounding box of the maze itself,
+        // which is more reliable than the grid size if lines were trimmed.
+        let tiles: Vec<Pos> = grid.positions(|c| c == b'#' || c == b'.').collect();
+        let left = tiles.iter().map(|p| p.0).min().unwrap_or(0);
+        let right = tiles.iter().map(|p| p.0).max().unwrap_or(0);
+        let top = tiles.iter().map(|p| p.1).min().unwrap_or(0);
+        let bottom = tiles.iter().map(|p| p.1).max().unwrap_or(0);
+
+        let mut portals = HashMap::new();
+        for first in grid.positions(|c| c.is_ascii_uppercase()) {
+            for dir in [Direction::Right, Direction::Down].iter() {
+                let second = match grid.step(first, *dir) {
+                    Some(second) if grid.get(second).unwrap().is_ascii_uppercase() => second,
+                    _ => continue,
+                };
+                let label: String = [first, second]
+                    .iter()
+                    .map(|pos| grid.get(*pos).unwrap() as char)
+                    .collect();
+
+                let before = grid.step(first, dir.reverse());
+                let after = grid.step(second, *dir);
+                let tile = [before, after]
+                    .iter()
+                    .flatten()
+                    .find(|pos| grid.get(**pos) == Some(b'.'))
+                    .cloned()
+                    .ok_or(format!("Portal {} is not next to the maze", label))?;
+
+                let outer = tile.0 == left || tile.0 == right || tile.1 == top || tile.1 == bottom;
+                let side = if outer { Side::Outer } else { Side::Inner };
+                portals.insert(tile, Portal { label, side });
+            }
+        }
+
+        let find = |label: &str| {
+            portals
+                .iter()
+                .find(|(_, portal)| portal.label == label)
+                .map(|(pos, _)| *pos)
+                .ok_or(format!("Missing portal {}", label))
+        };
+        let start = find("AA")?;
+        let end = find("ZZ")?;
+
+        let mut links = HashMap::new();
+        for (pos, portal) in portals.iter() {
+            let pair: Vec<Pos> = portals
+                .iter()
+                .filter(|(other, p)| *other != pos && p.label == portal.label)
+                .map(|(other, _)| *other)
+                .collect();
+            match pair[..] {
+                [other] => {
+                    links.insert(*pos, other);
+                }
+                [] if *pos == start || *pos == end => {}
+                _ => return Err(format!("Portal {} is not a pair", portal.label)),
+            }
+        }
+
+        Ok(Maze {
+            grid,
+            portals,
+            links,
+            start,
+            end,
+        })
+    }
+
+    fn neighbours(&self, (pos, level): State, mode: Mode) -> Vec<State> {
+        let mut next: Vec<State> = self
+            .grid
+            .neighbours(pos)
+            .filter(|n| self.grid.get(*n) == Some(b'.'))
+            .map(|n| (n, level))
+            .collect();
+
+        if let Some(other) = self.links.get(&pos) {
+            let side = self.portals[&pos].side;
+            match (mode, side) {
+                (Mode::Flat, _) => next.push((*other, level)),
+                (Mode::Recursive { max_depth }, Side::Inner) if level < max_depth => {
+                    next.push((*other, level + 1))
+                }
+                (Mode::Recursive { .. }, Side::Outer) if level > 0 => {
+                    next.push((*other, level - 1))
+                }
+                _ => {}
+            }
+        }
+        next
+    }
+
+    fn shortest(&self, mode: Mode) -> Option<Vec<State>> {
+        bfs(
+            (self.start, 0),
+            |state| self.neighbours(*state, mode),
+            |state| *state == (self.end, 0),
+        )
+    }
+
+    // Summarises a path as the walks between portals and the jumps through
+    // them.
+    fn describe(&self, path: &[State]) -> Vec<String> {
+        let label = |pos: &Pos| self.portals[pos].label.clone();
+        let mut lines = Vec::new();
+        let mut from = label(&self.start);
+        let mut steps = 0;
+
+        for pair in path.windows(2) {
+            let ((pos, level), (next, next_level)) = (pair[0], pair[1]);
+            let adjacent = pos.0.abs_diff(next.0) + pos.1.abs_diff(next.1) == 1;
+            if adjacent {
+                steps += 1;
+                continue;
+            }
+
+            let to = label(&pos);
+            lines.push(format!("Walk from {} to {} ({} steps)", from, to, steps));
+            lines.push(match next_level.cmp(&level) {
+                std::cmp::Ordering::Greater => {
+                    format!("Recurse into level {} through {} (1 step)", next_level, to)
+                }
+                std::cmp::Ordering::Less => {
+                    format!("Return to level {} through {} (1 step)", next_level, to)
+                }
+                std::cmp::Ordering::Equal => format!("Teleport through {} (1 step)", to),
+            });
+            from = to;
+            steps = 0;
+        }
+        lines.push(format!("Walk from {} to ZZ ({} steps)", from, steps));
+        lines
+    }
+}
+
 pub fn start(input: &str) {
-    println!("{}", input);
+    let maze = match Maze::parse(input) {
+        Ok(maze) => maze,
+        Err(e) => return println!("Could not parse the maze: {}", e),
+    };
+
+    let modes = [
+        ("Flat", Mode::Flat),
+        (
+            "Recursive",
+            Mode::Recursive {
+                max_depth: MAX_DEPTH,
+            },
+        ),
+    ];
+    for (name, mode) in modes.iter() {
+        match maze.shortest(*mode) {
+            Some(path) => {
+                println!("{}: {} steps", name, path.len() - 1);
+                for line in maze.describe(&path) {
+                    println!("  {}", line);
+                }
+            }
+            None => println!("{}: no path to ZZ", name),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SMALL: [&str; 19] = [
+        "         A           ",
+        "         A           ",
+        "  #######.#########  ",
+        "  #######.........#  ",
+        "  #######.#######.#  ",
+        "  #######.#######.#  ",
+        "  #######.#######.#  ",
+        "  #####  B    ###.#  ",
+        "BC...##  C    ###.#  ",
+        "  ##.##       ###.#  ",
+        "  ##...DE  F  ###.#  ",
+        "  #####    G  ###.#  ",
+        "  #########.#####.#  ",
+        "DE..#######...###.#  ",
+        "  #.#########.###.#  ",
+        "FG..#########.....#  ",
+        "  ###########.#####  ",
+        "             Z       ",
+        "             Z       ",
+    ];
+
+    // Only crossable by going down through BC and back up through DE.
+    const LEVELS: [&str; 15] = [
+        "       A     Z     ",
+        "       A     Z     ",
+        "  #####.#####.#    ",
+        "  #####.#####.#    ",
+        "  #####.#####.#    ",
+        "  ###...#####.#    ",
+        "  ###.     ##.#    ",
+        "  ###.BC DE...#    ",
+        "  ####     ####    ",
+        "  #############    ",
+        "BC.......######    ",
+        "  ######.######    ",
+        "  ######.######    ",
+        "        D          ",
+        "        E          ",
+    ];
+
+    fn steps(maze: &Maze, mode: Mode) -> Option<usize> {
+        maze.shortest(mode).map(|path| path.len() - 1)
+    }
+
+    #[test]
+    fn test_one() {
+        let maze = Maze::parse(&SMALL.join("\n")).unwrap();
+        assert_eq!(maze.portals.len(), 8);
+        assert_eq!(maze.portals[&(9, 6)].side, Side::Inner);
+        assert_eq!(maze.portals[&(2, 8)].side, Side::Outer);
+
+        let path = maze.shortest(Mode::Flat).unwrap();
+        assert_eq!(path.len() - 1, 23);
+        assert_eq!(
+            maze.describe(&path),
+            vec![
+                "Walk from AA to BC (4 steps)",
+                "Teleport through BC (1 step)",
+                "Walk from BC to DE (6 steps)",
+                "Teleport through DE (1 step)",
+                "Walk from DE to FG (4 steps)",
+                "Teleport through FG (1 step)",
+                "Walk from FG to ZZ (6 steps)",
+            ]
+        );
+
+        // The same maze with trailing whitespace stripped.
+        let trimmed: Vec<&str> = SMALL.iter().map(|line| line.trim_end()).collect();
+        let maze = Maze::parse(&trimmed.join("\n")).unwrap();
+        assert_eq!(steps(&maze, Mode::Flat), Some(23));
+    }
+
+    #[test]
+    fn test_two() {
+        let maze = Maze::parse(&SMALL.join("\n")).unwrap();
+        assert_eq!(steps(&maze, Mode::Recursive { max_depth: 10 }), Some(26));
+
+        let maze = Maze::parse(&LEVELS.join("\n")).unwrap();
+        assert_eq!(steps(&maze, Mode::Flat), Some(24));
+        let path = maze.shortest(Mode::Recursive { max_depth: 1 }).unwrap();
+        assert_eq!(
+            maze.describe(&path),
+            vec![
+                "Walk from AA to BC (7 steps)",
+                "Recurse into level 1 through BC (1 step)",
+                "Walk from BC to DE (8 steps)",
+                "Return to level 0 through DE (1 step)",
+                "Walk from DE to ZZ (7 steps)",
+            ]
+        );
+        assert_eq!(steps(&maze, Mode::Recursive { max_depth: 0 }), None);
+    }
 }
@@ -1,3 +1,443 @@
-pub fn start(input: &str) {
-    println!("{}", input);
+use crate::intcode::{IntCodeProgram, IntCodeStatus};
+use std::collections::HashMap;
+use std::io::{self, BufRead, Write};
+
+// Items that end the game, or leave the droid stuck, when picked up.
+const DEADLY: [&str; 5] = [
+    "infinite loop",
+    "giant electromagnet",
+    "photons",
+    "escape pod",
+    "molten lava",
+];
+// Cracking the floor tries every combination of items, so refuse to try more
+// than a million of them.
+const MAX_ITEMS: usize = 20;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+struct Room {
+    name: String,
+    description: String,
+    doors: Vec<String>,
+    items: Vec<String>,
+}
+
+// Reads the last room described in the output, which is where the droid ended
+// up if it was moved on from somewhere else.
+fn parse_room(output: &str) -> Option<Room> {
+    let start = output.rfind("== ")?;
+    let mut lines = output[start..].lines();
+    let name = lines.next()?.trim_matches(|c| c == '=' || c == ' ');
+    let mut room = Room {
+        name: name.to_string(),
+        ..Room::default()
+    };
+
+    let mut section = None;
+    for line in lines.map(str::trim) {
+        match line {
+            "" => section = None,
+            "Doors here lead:" => section = Some(&mut room.doors),
+            "Items here:" => section = Some(&mut room.items),
+            "Command?" => break,
+            _ => match (&mut section, line.strip_prefix("- ")) {
+                (Some(list), Some(entry)) => list.push(entry.to_string()),
+                _ if room.description.is_empty() => room.description = line.to_string(),
+                _ => {}
+            },
+        }
+    }
+    Some(room)
+}
+
+fn reverse(door: &str) -> &str {
+    match door {
+        "north" => "south",
+        "south" => "north",
+        "east" => "west",
+        "west" => "east",
+        _ => door,
+    }
+}
+
+struct Droid {
+    program: IntCodeProgram,
+    // The route from the start to each room found so far.
+    routes: HashMap<String, Vec<String>>,
+    inventory: Vec<String>,
+    // The room the pressure-sensitive floor throws the droid back to, and the
+    // door to the floor.
+    checkpoint: Option<(String, String)>,
+}
+
+impl Droid {
+    fn new(program: IntCodeProgram) -> Droid {
+        Droid {
+            program,
+            routes: HashMap::new(),
+            inventory: Vec::new(),
+            checkpoint: None,
+        }
+    }
+
+    fn send(&mut self, command: &str) -> String {
+        self.program
+            .in_buf
+            .extend(command.bytes().chain(Some(b'\n')).map(i64::from));
+        self.output()
+    }
+
+    fn output(&mut self) -> String {
+        self.program.run();
+        let output = self
+            .program
+            .out_buf
+            .iter()
+            .filter(|c| (0..128).contains(*c))
+            .map(|c| *c as u8 as char)
+            .collect();
+        self.program.out_buf.clear();
+        output
+    }
+
+    fn is_running(&self) -> bool {
+        self.program.status == IntCodeStatus::AwaitingInput
+    }
+
+    // Depth-first walk over the ship that picks up everything safe and leaves
+    // the droid back where it started.
+    fn explore(&mut self, room: &Room, route: Vec<String>) -> Result<(), String> {
+        self.routes.insert(room.name.clone(), route.clone());
+        for item in room.items.iter() {
+            if DEADLY.contains(&item.as_str()) {
+                continue;
+            }
+            self.send(&format!("take {}", item));
+            if !self.is_running() {
+                return Err(format!("Picking up the {} was fatal", item));
+            }
+            self.inventory.push(item.clone());
+        }
+
+        for door in room.doors.iter() {
+            let next = parse_room(&self.send(door))
+                .ok_or(format!("Lost going {} from {}", door, room.name))?;
+            if next.name == room.name {
+                self.checkpoint = Some((room.name.clone(), door.clone()));
+                continue;
+            }
+            if !self.routes.contains_key(&next.name) {
+                let mut next_route = route.clone();
+                next_route.push(door.clone());
+                self.explore(&next, next_route)?;
+            }
+            self.send(reverse(door));
+        }
+        Ok(())
+    }
+
+    // Tries every combination of items on the floor, changing one item at a
+    // time by following a Gray code.
+    fn crack(&mut self, door: &str) -> Result<String, String> {
+        let items = self.inventory.clone();
+        if items.len() > MAX_ITEMS {
+            return Err(format!("Too many items to try them all: {}", items.len()));
+        }
+        for i in 0..1u32 << items.len() {
+            if i > 0 {
+                let item = &items[i.trailing_zeros() as usize];
+                let gray = i ^ (i >> 1);
+                if gray & (1 << i.trailing_zeros()) != 0 {
+                    self.send(&format!("drop {}", item));
+                } else {
+                    self.send(&format!("take {}", item));
+                }
+            }
+
+            let output = self.send(door);
+            if !output.contains("Alert!") {
+                return Ok(password(&output).unwrap_or(output));
+            }
+        }
+        Err(String::from("No combination of items got past the floor"))
+    }
+}
+
+fn password(output: &str) -> Option<String> {
+    let start = output.find("typing ")? + "typing ".len();
+    let digits: String = output[start..]
+        .chars()
+        .take_while(char::is_ascii_digit)
+        .collect();
+    Some(digits).filter(|d| !d.is_empty())
+}
+
+fn solve(program: IntCodeProgram) -> Result<String, String> {
+    let mut droid = Droid::new(program);
+    let output = droid.output();
+    let start = parse_room(&output).ok_or("No starting room")?;
+    droid.explore(&start, Vec::new())?;
+
+    let (checkpoint, door) = droid
+        .checkpoint
+        .clone()
+        .ok_or("No pressure-sensitive floor")?;
+    for door in droid.routes[&checkpoint].clone() {
+        droid.send(&door);
+    }
+    droid.crack(&door)
+}
+
+// Relays commands from stdin until the game ends.
+fn play(program: IntCodeProgram) {
+    let mut droid = Droid::new(program);
+    print!("{}", droid.output());
+    let stdin = io::stdin();
+    let mut lines = stdin.lock().lines();
+    while droid.is_running() {
+        io::stdout().flush().ok();
+        let command = match lines.next() {
+            Some(Ok(command)) => command,
+            _ => return,
+        };
+        print!("{}", droid.send(command.trim()));
+    }
+}
+
+pub fn start(input: &str, args: &[String]) {
+    let program = IntCodeProgram::from_input(input);
+    match args {
+        [] => match solve(program) {
+            Ok(password) => println!("Password: {}", password),
+            Err(e) => println!("{}", e),
+        },
+        [flag] if flag == "--play" => play(program),
+        _ => println!("Usage: twentyfive [--play]"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intcode::compiler::compile;
+
+    const HULL_BREACH: &str = "\n\n\n== Hull Breach ==\n\
+                               You got in through a hole in the floor here.\n\n\
+                               Doors here lead:\n- north\n- east\n\n";
+
+    // Statements printing `text` one character at a time.
+    fn say(text: &str) -> String {
+        text.bytes().map(|c| format!("output({});", c)).collect()
+    }
+
+    // A five room ship. Items are tracked by where they are, with 99 meaning
+    // the droid is carrying them, and told apart by their first letter. Only
+    // the coin and the spool together weigh enough for the floor.
+    fn game() -> IntCodeProgram {
+        let rooms = [
+            HULL_BREACH.to_string(),
+            "\n\n\n== Galley ==\nIt smells of old coffee.\n\nDoors here lead:\n- south\n\n"
+                .to_string(),
+            "\n\n\n== Hallway ==\nA long corridor.\n\nDoors here lead:\n- north\n- east\n\
+             - west\n\n"
+                .to_string(),
+            "\n\n\n== Storage ==\nShelves line the walls.\n\nDoors here lead:\n- south\n\n"
+                .to_string(),
+            "\n\n\n== Security Checkpoint ==\nIn the next room, a pressure-sensitive floor \
+             will verify your identity.\n\nDoors here lead:\n- east\n- west\n\n"
+                .to_string(),
+        ];
+        let ejected = |weight: &str| {
+            say(&format!(
+                "\n\n\n== Pressure-Sensitive Floor ==\nAnalyzing...\n\nDoors here lead:\n\
+                 - west\n\nA loud, robotic voice says \"Alert! Droids on this ship are {} \
+                 than the detected value!\" and you are ejected back to the checkpoint.\n",
+                weight
+            ))
+        };
+
+        let show_rooms: String = rooms
+            .iter()
+            .enumerate()
+            .map(|(i, text)| format!("if (r == {}) {{ {} }}", i, say(text)))
+            .collect();
+        let items = [
+            ("c", "coin"),
+            ("s", "spool of cat6"),
+            ("h", "hypercube"),
+            ("m", "molten lava"),
+        ];
+        let list_items: String = items
+            .iter()
+            .map(|(var, name)| {
+                let line = say(&format!("- {}\n", name));
+                format!("if ({} == r) {{ {} }}", var, line)
+            })
+            .collect();
+        let any_items: String = items
+            .iter()
+            .map(|(var, _)| format!("if ({} == r) {{ any = 1; }}", var))
+            .collect();
+        let take: String = items
+            .iter()
+            .map(|(var, name)| {
+                let taken = if *var == "m" {
+                    format!(
+                        "{} done = 1;",
+                        say("\nThe molten lava is way too hot! You melt!\n")
+                    )
+                } else {
+                    format!(
+                        "{} = 99; {}",
+                        var,
+                        say(&format!("\nYou take the {}.\n\n", name))
+                    )
+                };
+                format!(
+                    "if (k == {}) {{ if ({} == r) {{ {} }} else {{ {} }} }}",
+                    var.as_bytes()[0],
+                    var,
+                    taken,
+                    say("\nYou don't see that item here.\n\n")
+                )
+            })
+            .collect();
+        let drop: String = items
+            .iter()
+            .map(|(var, name)| {
+                format!(
+                    "if (k == {}) {{ if ({} == 99) {{ {} = r; {} }} }}",
+                    var.as_bytes()[0],
+                    var,
+                    var,
+                    say(&format!("\nYou drop the {}.\n\n", name))
+                )
+            })
+            .collect();
+
+        let source = format!(
+            "fn show(r, c, s, h, m) {{
+                 {show_rooms}
+                 let any = 0;
+                 {any_items}
+                 if (any == 1) {{ {items_here} {list_items} {blank} }}
+             }}
+
+             fn main() {{
+                 let r = 0;
+                 let c = 1;
+                 let s = 2;
+                 let h = 3;
+                 let m = 2;
+                 let done = 0;
+                 let first = 0;
+                 let k = 0;
+                 let col = 0;
+                 let ch = 0;
+                 let next = 0;
+                 let w = 0;
+                 show(r, c, s, h, m);
+                 while (done == 0) {{
+                     {prompt}
+                     col = 0;
+                     ch = 0;
+                     while (ch != 10) {{
+                         ch = input();
+                         if (col == 0) {{ first = ch; }}
+                         if (col == 5) {{ k = ch; }}
+                         col = col + 1;
+                     }}
+                     next = 9;
+                     if (first == 110) {{ if (r == 0) {{ next = 1; }} if (r == 2) {{ next = 3; }} }}
+                     if (first == 115) {{ if (r == 1) {{ next = 0; }} if (r == 3) {{ next = 2; }} }}
+                     if (first == 101) {{
+                         if (r == 0) {{ next = 2; }}
+                         if (r == 2) {{ next = 4; }}
+                         if (r == 4) {{ next = 5; }}
+                     }}
+                     if (first == 119) {{ if (r == 2) {{ next = 0; }} if (r == 4) {{ next = 2; }} }}
+                     if (first == 116) {{ {take} }}
+                     if (first == 100) {{ {drop} }}
+                     if (next == 5) {{
+                         w = 0;
+                         if (c == 99) {{ w = w + 1; }}
+                         if (s == 99) {{ w = w + 2; }}
+                         if (h == 99) {{ w = w + 4; }}
+                         if (w == 3) {{
+                             {success}
+                             done = 1;
+                         }} else {{
+                             if (w > 3) {{ {heavier} }} else {{ {lighter} }}
+                             show(r, c, s, h, m);
+                         }}
+                     }} else {{
+                         if (next != 9) {{
+                             r = next;
+                             show(r, c, s, h, m);
+                         }}
+                     }}
+                 }}
+             }}",
+            show_rooms = show_rooms,
+            any_items = any_items,
+            items_here = say("Items here:\n"),
+            list_items = list_items,
+            blank = say("\n"),
+            prompt = say("Command?\n"),
+            take = take,
+            drop = drop,
+            success = say(
+                "\n\n\n== Pressure-Sensitive Floor ==\nAnalyzing...\n\nDoors here lead:\n\
+                 - west\n\nA loud, robotic voice says \"Analysis complete! You may proceed.\" \
+                 and you enter the cockpit.\n\"Oh, hello! You should be able to get in by \
+                 typing 2424 on the keypad at the main airlock.\"\n"
+            ),
+            heavier = ejected("heavier"),
+            lighter = ejected("lighter"),
+        );
+        IntCodeProgram::from_memory(compile(&source).unwrap())
+    }
+
+    #[test]
+    fn test_one() {
+        let room =
+            parse_room(&format!("{}Items here:\n- coin\n\nCommand?\n", HULL_BREACH)).unwrap();
+        assert_eq!(
+            room,
+            Room {
+                name: String::from("Hull Breach"),
+                description: String::from("You got in through a hole in the floor here."),
+                doors: vec![String::from("north"), String::from("east")],
+                items: vec![String::from("coin")],
+            }
+        );
+        assert_eq!(parse_room("Command?\n"), None);
+
+        let mut droid = Droid::new(game());
+        assert_eq!(parse_room(&droid.output()).unwrap().name, "Hull Breach");
+        let hallway = parse_room(&droid.send("east")).unwrap();
+        assert_eq!(hallway.items, vec!["spool of cat6", "molten lava"]);
+        droid.send("take molten lava");
+        assert!(!droid.is_running());
+    }
+
+    #[test]
+    fn test_two() {
+        let mut droid = Droid::new(game());
+        let start = parse_room(&droid.output()).unwrap();
+        droid.explore(&start, Vec::new()).unwrap();
+        assert_eq!(droid.routes.len(), 5);
+        assert_eq!(droid.routes["Storage"], vec!["east", "north"]);
+        assert_eq!(droid.inventory, vec!["coin", "spool of cat6", "hypercube"]);
+        assert_eq!(
+            droid.checkpoint,
+            Some((String::from("Security Checkpoint"), String::from("east")))
+        );
+
+        assert_eq!(solve(game()), Ok(String::from("2424")));
+
+        let mut droid = Droid::new(game());
+        droid.inventory = (0..32).map(|i| format!("item {}", i)).collect();
+        assert!(droid.crack("east").is_err());
+    }
 }
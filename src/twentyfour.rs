@@ -1,3 +1,236 @@
-pub fn start(input: &str) {
-    println!("{}", input);
+use std::collections::{BTreeMap, HashSet};
+
+const SIZE: usize = 5;
+const TILES: usize = SIZE * SIZE;
+const CENTRE: usize = TILES / 2;
+const MINUTES: usize = 200;
+
+// One bit per tile in reading order, so a layout is also its biodiversity
+// rating.
+type Layout = u32;
+
+// Bugs on each level of the recursive grid, where level 1 is the grid inside
+// level 0's centre tile and level -1 is the one whose centre holds level 0.
+type Levels = BTreeMap<i32, Layout>;
+
+fn parse(input: &str) -> Result<Layout, String> {
+    let rows: Vec<&str> = input
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+        .collect();
+    if rows.len() != SIZE || rows.iter().any(|row| row.len() != SIZE) {
+        return Err(format!("Expected a {}x{} grid", SIZE, SIZE));
+    }
+
+    let mut layout = 0;
+    for (i, c) in rows.iter().flat_map(|row| row.bytes()).enumerate() {
+        match c {
+            b'#' => layout |= 1 << i,
+            b'.' => {}
+            _ => return Err(format!("Unexpected tile: {}", c as char)),
+        }
+    }
+    Ok(layout)
+}
+
+fn is_bug(layout: Layout, tile: usize) -> bool {
+    layout & (1 << tile) != 0
+}
+
+// A bug survives with exactly one neighbour, and an empty tile becomes
+// infested with one or two.
+fn lives(bug: bool, neighbours: u32) -> bool {
+    neighbours == 1 || (!bug && neighbours == 2)
+}
+
+fn adjacent(tile: usize) -> impl Iterator<Item = usize> {
+    let (x, y) = (tile % SIZE, tile / SIZE);
+    let mut tiles = Vec::new();
+    if y > 0 {
+        tiles.push(tile - SIZE);
+    }
+    if y + 1 < SIZE {
+        tiles.push(tile + SIZE);
+    }
+    if x > 0 {
+        tiles.push(tile - 1);
+    }
+    if x + 1 < SIZE {
+        tiles.push(tile + 1);
+    }
+    tiles.into_iter()
+}
+
+fn step(layout: Layout) -> Layout {
+    (0..TILES)
+        .filter(|tile| {
+            let neighbours = adjacent(*tile).filter(|n| is_bug(layout, *n)).count();
+            lives(is_bug(layout, *tile), neighbours as u32)
+        })
+        .fold(0, |next, tile| next | (1 << tile))
+}
+
+fn first_repeat(mut layout: Layout) -> Layout {
+    let mut seen = HashSet::new();
+    while seen.insert(layout) {
+        layout = step(layout);
+    }
+    layout
+}
+
+// Neighbours of a tile across levels, as `(level offset, tile)`. Edges look
+// out to the tiles around the enclosing grid's centre, and the tiles around
+// the centre look in to a whole edge of the nested grid.
+fn recursive_neighbours(tile: usize) -> Vec<(i32, usize)> {
+    let (x, y) = (tile % SIZE, tile / SIZE);
+    let mut neighbours: Vec<(i32, usize)> = adjacent(tile)
+        .filter(|n| *n != CENTRE)
+        .map(|n| (0, n))
+        .collect();
+
+    if y == 0 {
+        neighbours.push((-1, CENTRE - SIZE));
+    }
+    if y + 1 == SIZE {
+        neighbours.push((-1, CENTRE + SIZE));
+    }
+    if x == 0 {
+        neighbours.push((-1, CENTRE - 1));
+    }
+    if x + 1 == SIZE {
+        neighbours.push((-1, CENTRE + 1));
+    }
+
+    let inner_edge: Vec<usize> = if tile == CENTRE - SIZE {
+        (0..SIZE).collect()
+    } else if tile == CENTRE + SIZE {
+        (TILES - SIZE..TILES).collect()
+    } else if tile == CENTRE - 1 {
+        (0..SIZE).map(|i| i * SIZE).collect()
+    } else if tile == CENTRE + 1 {
+        (0..SIZE).map(|i| i * SIZE + SIZE - 1).collect()
+    } else {
+        Vec::new()
+    };
+    neighbours.extend(inner_edge.into_iter().map(|n| (1, n)));
+    neighbours
+}
+
+fn recursive_step(levels: &Levels) -> Levels {
+    let neighbours: Vec<Vec<(i32, usize)>> = (0..TILES).map(recursive_neighbours).collect();
+    let (min, max) = match (levels.keys().next(), levels.keys().next_back()) {
+        (Some(min), Some(max)) => (*min, *max),
+        _ => return Levels::new(),
+    };
+
+    let mut next = Levels::new();
+    for level in min - 1..=max + 1 {
+        let layout = levels.get(&level).cloned().unwrap_or(0);
+        let grid = (0..TILES)
+            .filter(|tile| *tile != CENTRE)
+            .filter(|tile| {
+                let count = neighbours[*tile]
+                    .iter()
+                    .filter(|(offset, n)| {
+                        let other = levels.get(&(level + offset)).cloned().unwrap_or(0);
+                        is_bug(other, *n)
+                    })
+                    .count();
+                lives(is_bug(layout, *tile), count as u32)
+            })
+            .fold(0, |grid, tile| grid | (1 << tile));
+        if grid != 0 {
+            next.insert(level, grid);
+        }
+    }
+    next
+}
+
+fn recursive_bugs(layout: Layout, minutes: usize) -> Levels {
+    let mut levels = Levels::new();
+    levels.insert(0, layout & !(1 << CENTRE));
+    for _ in 0..minutes {
+        levels = recursive_step(&levels);
+    }
+    levels
+}
+
+fn count_bugs(levels: &Levels) -> u32 {
+    levels.values().map(|layout| layout.count_ones()).sum()
+}
+
+// Draws a layout, marking the centre with `?` when it holds a nested grid.
+fn render(layout: Layout, recursive: bool) -> String {
+    let mut out = String::new();
+    for tile in 0..TILES {
+        out.push(match tile {
+            CENTRE if recursive => '?',
+            _ if is_bug(layout, tile) => '#',
+            _ => '.',
+        });
+        if tile % SIZE == SIZE - 1 {
+            out.push('\n');
+        }
+    }
+    out
+}
+
+pub fn start(input: &str, args: &[String]) {
+    let level = match args {
+        [] => 0,
+        [flag, level] if flag == "--level" => match level.parse() {
+            Ok(level) => level,
+            Err(_) => return println!("Invalid level: {}", level),
+        },
+        _ => return println!("Usage: twentyfour [--level N]"),
+    };
+    let layout = match parse(input) {
+        Ok(layout) => layout,
+        Err(e) => return println!("{}", e),
+    };
+
+    let repeat = first_repeat(layout);
+    println!("First repeated layout:\n{}", render(repeat, false));
+    println!("Biodiversity rating: {}", repeat);
+
+    let levels = recursive_bugs(layout, MINUTES);
+    println!("Bugs after {} minutes: {}", MINUTES, count_bugs(&levels));
+    let grid = levels.get(&level).cloned().unwrap_or(0);
+    println!("Level {}:\n{}", level, render(grid, true));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const EXAMPLE: &str = "....#\n#..#.\n#..##\n..#..\n#....";
+
+    #[test]
+    fn test_one() {
+        let layout = parse(EXAMPLE).unwrap();
+        assert_eq!(render(layout, false), format!("{}\n", EXAMPLE));
+        assert_eq!(
+            render(step(layout), false),
+            "#..#.\n####.\n###.#\n##.##\n.##..\n"
+        );
+        assert_eq!(first_repeat(layout), 2129920);
+        assert!(parse("#....\n.....").is_err());
+    }
+
+    #[test]
+    fn test_two() {
+        assert_eq!(recursive_neighbours(0).len(), 4);
+        assert_eq!(recursive_neighbours(CENTRE - 1).len(), 8);
+        assert_eq!(recursive_neighbours(6).len(), 4);
+
+        let levels = recursive_bugs(parse(EXAMPLE).unwrap(), 10);
+        assert_eq!(count_bugs(&levels), 99);
+        assert_eq!(levels.keys().next(), Some(&-5));
+        assert_eq!(levels.keys().next_back(), Some(&5));
+        assert_eq!(
+            render(levels[&0], true),
+            ".#...\n.#.##\n.#?..\n.....\n.....\n"
+        );
+    }
 }
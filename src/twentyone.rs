@@ -1,3 +1,544 @@
+use crate::intcode::{IntCodeProgram, IntCodeStatus};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+// The springdroid's memory only holds this many instructions.
+const MAX_INSTRUCTIONS: usize = 15;
+// How many deaths to learn from before giving up on a mode.
+const MAX_ATTEMPTS: usize = 50;
+// Stops the synthesizer from exhausting memory on hopeless hull sets.
+const MAX_STATES: usize = 2_000_000;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Mode {
+    Walk,
+    Run,
+}
+
+impl Mode {
+    // How many tiles ahead the droid can see, i.e. registers A onwards.
+    fn range(self) -> usize {
+        match self {
+            Mode::Walk => 4,
+            Mode::Run => 9,
+        }
+    }
+}
+
+impl fmt::Display for Mode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Mode::Walk => write!(f, "WALK"),
+            Mode::Run => write!(f, "RUN"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Op {
+    And,
+    Or,
+    Not,
+}
+
+// Sensors are numbered from 0 for A, the tile directly ahead.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Register {
+    Sensor(usize),
+    T,
+    J,
+}
+
+impl Register {
+    fn parse(s: &str) -> Option<Register> {
+        match s.as_bytes() {
+            b"T" => Some(Register::T),
+            b"J" => Some(Register::J),
+            [c @ b'A'..=b'I'] => Some(Register::Sensor((c - b'A') as usize)),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Register {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Register::Sensor(i) => write!(f, "{}", (b'A' + *i as u8) as char),
+            Register::T => write!(f, "T"),
+            Register::J => write!(f, "J"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+struct Instruction {
+    op: Op,
+    x: Register,
+    y: Register,
+}
+
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let op = match self.op {
+            Op::And => "AND",
+            Op::Or => "OR",
+            Op::Not => "NOT",
+        };
+        write!(f, "{} {} {}", op, self.x, self.y)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Script {
+    mode: Mode,
+    instructions: Vec<Instruction>,
+}
+
+impl Script {
+    fn parse(source: &str, mode: Mode) -> Result<Script, String> {
+        let mut instructions = Vec::new();
+        for line in source.lines().map(str::trim).filter(|l| !l.is_empty()) {
+            let words: Vec<&str> = line.split_whitespace().collect();
+            let (op, x, y) = match words[..] {
+                [op, x, y] => (op, x, y),
+                _ => return Err(format!("Expected OP X Y: {}", line)),
+            };
+            let op = match op {
+                "AND" => Op::And,
+                "OR" => Op::Or,
+                "NOT" => Op::Not,
+                _ => return Err(format!("Unknown instruction: {}", line)),
+            };
+            let x = match Register::parse(x) {
+                Some(Register::Sensor(i)) if i >= mode.range() => {
+                    return Err(format!("{} can't be read in {} mode", x, mode))
+                }
+                Some(x) => x,
+                None => return Err(format!("Unknown register: {}", x)),
+            };
+            let y = match Register::parse(y) {
+                Some(Register::Sensor(_)) => return Err(format!("{} can't be written", y)),
+                Some(y) => y,
+                None => return Err(format!("Unknown register: {}", y)),
+            };
+            instructions.push(Instruction { op, x, y });
+        }
+        if instructions.len() > MAX_INSTRUCTIONS {
+            return Err(format!(
+                "{} instructions is over the limit of {}",
+                instructions.len(),
+                MAX_INSTRUCTIONS
+            ));
+        }
+        Ok(Script { mode, instructions })
+    }
+
+    fn to_input(&self) -> Vec<i64> {
+        self.to_string().bytes().map(i64::from).collect()
+    }
+
+    // Runs the script locally against one set of sensor readings, where bit
+    // `i` is set if there is ground `i + 1` tiles ahead.
+    fn jumps(&self, sensors: u16) -> bool {
+        let (mut t, mut j) = (false, false);
+        for instr in self.instructions.iter() {
+            let x = match instr.x {
+                Register::Sensor(i) => sensors & (1 << i) != 0,
+                Register::T => t,
+                Register::J => j,
+            };
+            let y = if instr.y == Register::T {
+                &mut t
+            } else {
+                &mut j
+            };
+            *y = match instr.op {
+                Op::And => x && *y,
+                Op::Or => x || *y,
+                Op::Not => !x,
+            };
+        }
+        j
+    }
+}
+
+impl fmt::Display for Script {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for instr in self.instructions.iter() {
+            writeln!(f, "{}", instr)?;
+        }
+        writeln!(f, "{}", self.mode)
+    }
+}
+
+// Hand-written scripts: jump if there's a hole in the next three tiles and
+// ground to land on, and when running, only if the droid can carry on from
+// there by stepping or jumping again.
+const SCRIPTS: [(Mode, &str); 2] = [
+    (
+        Mode::Walk,
+        "NOT A J\nNOT B T\nOR T J\nNOT C T\nOR T J\nAND D J",
+    ),
+    (
+        Mode::Run,
+        "NOT A J\nNOT B T\nOR T J\nNOT C T\nOR T J\nAND D J\n\
+         NOT E T\nNOT T T\nOR H T\nAND T J",
+    ),
+];
+
 pub fn start(input: &str) {
-    println!("{}", input);
+    let program = IntCodeProgram::from_input(input);
+    let mut hulls = Vec::new();
+
+    for (mode, source) in SCRIPTS.iter() {
+        let script = match Script::parse(source, *mode) {
+            Ok(script) => script,
+            Err(e) => return println!("Invalid {} script: {}", mode, e),
+        };
+        match solve(&program, script, &mut hulls) {
+            Ok(Some((script, damage))) => {
+                println!("{} script:\n{}", mode, script);
+                println!("Hull damage: {}", damage);
+            }
+            Ok(None) => println!("No {} script found", mode),
+            Err(status) => return println!("The droid failed: {:?}", status),
+        }
+    }
+}
+
+// Tries the script on the droid, and every time the droid falls adds the hull
+// it fell through to the ones the next script has to cross. A walking droid's
+// hulls still apply when running, so they are shared between modes. Fails
+// with the droid's status if its program doesn't halt.
+fn solve(
+    program: &IntCodeProgram,
+    mut script: Script,
+    hulls: &mut Vec<Vec<bool>>,
+) -> Result<Option<(Script, i64)>, IntCodeStatus> {
+    for _ in 0..MAX_ATTEMPTS {
+        if failures(&script, hulls).is_empty() {
+            let animation = match run_droid(program, &script)? {
+                Run::Survived(damage) => return Ok(Some((script, damage))),
+                Run::Fell(animation) => animation,
+            };
+            println!("{}", animation);
+
+            match death_hull(&animation) {
+                Some(hull) if !hulls.contains(&hull) => hulls.push(hull),
+                _ => return Ok(None),
+            }
+        }
+        script = match synthesize(hulls, script.mode) {
+            Some(script) => script,
+            None => return Ok(None),
+        };
+    }
+    Ok(None)
+}
+
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum Run {
+    // The hull damage reported.
+    Survived(i64),
+    // The animation of the droid's last moments.
+    Fell(String),
+}
+
+// Fails with the droid's status if it stops without halting.
+fn run_droid(program: &IntCodeProgram, script: &Script) -> Result<Run, IntCodeStatus> {
+    let mut droid = program.clone();
+    droid.in_buf = script.to_input();
+    droid.run();
+    if droid.status != IntCodeStatus::Halted {
+        return Err(droid.status);
+    }
+    match droid.out_buf.last() {
+        Some(damage) if *damage > 127 => Ok(Run::Survived(*damage)),
+        _ => Ok(Run::Fell(
+            droid
+                .out_buf
+                .iter()
+                .filter(|c| (0..128).contains(*c))
+                .map(|c| *c as u8 as char)
+                .collect(),
+        )),
+    }
+}
+
+// The hull is the last row of the animation drawn before the droid fell into
+// it, with the droid starting above its first tile.
+fn death_hull(animation: &str) -> Option<Vec<bool>> {
+    animation
+        .lines()
+        .rev()
+        .find(|line| line.contains('#') && line.bytes().all(|c| c == b'#' || c == b'.'))
+        .map(parse_hull)
+}
+
+fn parse_hull(line: &str) -> Vec<bool> {
+    line.bytes().map(|c| c == b'#').collect()
+}
+
+fn sensors(hull: &[bool], pos: usize, range: usize) -> u16 {
+    (0..range)
+        .filter(|i| *hull.get(pos + i + 1).unwrap_or(&true))
+        .fold(0, |mask, i| mask | (1 << i))
+}
+
+// Walks the droid across the hull, jumping four tiles whenever `jumps` says
+// so. Everything past the end of the hull is ground.
+fn survives(hull: &[bool], range: usize, jumps: impl Fn(u16) -> bool) -> bool {
+    let mut pos = 0;
+    while pos < hull.len() {
+        if !hull[pos] {
+            return false;
+        }
+        pos += if jumps(sensors(hull, pos, range)) {
+            4
+        } else {
+            1
+        };
+    }
+    true
+}
+
+// Hulls the script drops the droid through.
+fn failures<'a>(script: &Script, hulls: &'a [Vec<bool>]) -> Vec<&'a [bool]> {
+    hulls
+        .iter()
+        .filter(|hull| !survives(hull, script.mode.range(), |s| script.jumps(s)))
+        .map(|hull| &hull[..])
+        .collect()
+}
+
+// Breadth-first search for the shortest script that crosses every hull.
+//
+// Only the sensor readings the hulls can produce matter, so a partial script
+// is summed up by the values of T and J for each of them, as bitmasks. Scripts
+// that agree on those are interchangeable and only the first is kept.
+fn synthesize(hulls: &[Vec<bool>], mode: Mode) -> Option<Script> {
+    let range = mode.range();
+    let mut readings: Vec<u16> = hulls
+        .iter()
+        .flat_map(|hull| (0..hull.len()).map(move |pos| sensors(hull, pos, range)))
+        .collect();
+    readings.sort_unstable();
+    readings.dedup();
+    if readings.len() > 128 {
+        return None;
+    }
+    let index: HashMap<u16, usize> = readings.iter().enumerate().map(|(i, r)| (*r, i)).collect();
+    let all = readings
+        .iter()
+        .enumerate()
+        .fold(0u128, |m, (i, _)| m | (1 << i));
+
+    let mut registers: Vec<(Register, u128)> = (0..range)
+        .map(|s| {
+            let mask = readings
+                .iter()
+                .enumerate()
+                .filter(|(_, r)| *r & (1 << s) != 0)
+                .fold(0, |m, (i, _)| m | (1 << i));
+            (Register::Sensor(s), mask)
+        })
+        .collect();
+    registers.push((Register::T, 0));
+    registers.push((Register::J, 0));
+
+    let crosses = |j: u128| {
+        hulls
+            .iter()
+            .all(|hull| survives(hull, range, |s| j & (1 << index[&s]) != 0))
+    };
+
+    type State = (u128, u128);
+    let start: State = (0, 0);
+    let mut parents: HashMap<State, (State, Instruction)> = HashMap::new();
+    let mut checked: HashSet<u128> = HashSet::new();
+    let mut frontier = vec![start];
+    let mut found = if crosses(0) { Some(start) } else { None };
+    checked.insert(0);
+
+    for _ in 0..MAX_INSTRUCTIONS {
+        if found.is_some() {
+            break;
+        }
+        let mut next = Vec::new();
+        'search: for state in frontier.iter() {
+            for (x, value) in registers.iter() {
+                let x_value = match x {
+                    Register::T => state.0,
+                    Register::J => state.1,
+                    _ => *value,
+                };
+                for op in [Op::And, Op::Or, Op::Not].iter() {
+                    for y in [Register::T, Register::J].iter() {
+                        let y_value = if *y == Register::T { state.0 } else { state.1 };
+                        let result = match op {
+                            Op::And => x_value & y_value,
+                            Op::Or => x_value | y_value,
+                            Op::Not => !x_value & all,
+                        };
+                        let new = if *y == Register::T {
+                            (result, state.1)
+                        } else {
+                            (state.0, result)
+                        };
+                        if new == start || parents.contains_key(&new) {
+                            continue;
+                        }
+                        let instr = Instruction {
+                            op: *op,
+                            x: *x,
+                            y: *y,
+                        };
+                        parents.insert(new, (*state, instr));
+                        next.push(new);
+
+                        if checked.insert(new.1) && crosses(new.1) {
+                            found = Some(new);
+                            break 'search;
+                        }
+                    }
+                }
+            }
+        }
+        if next.is_empty() || parents.len() > MAX_STATES {
+            return None;
+        }
+        frontier = next;
+    }
+
+    let mut state = found?;
+    let mut instructions = Vec::new();
+    while let Some((parent, instr)) = parents.get(&state) {
+        instructions.push(*instr);
+        state = *parent;
+    }
+    instructions.reverse();
+    Some(Script { mode, instructions })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intcode::compiler::compile;
+
+    // Reads the script a line at a time until the WALK or RUN line. Reports
+    // "damage" of 1000 plus the line count if there were any instructions,
+    // otherwise draws a droid falling into a hole.
+    const DROID: &str = "fn main() {
+                             let lines = 0;
+                             let first = 0;
+                             let second = 0;
+                             let col = 0;
+                             let done = 0;
+                             let c = 0;
+                             while (done == 0) {
+                                 c = input();
+                                 if (c == 10) {
+                                     if (first == 87) { done = 1; }
+                                     if (first == 82) { if (second == 85) { done = 1; } }
+                                     lines = lines + 1;
+                                     col = 0;
+                                 } else {
+                                     if (col == 0) { first = c; }
+                                     if (col == 1) { second = c; }
+                                     col = col + 1;
+                                 }
+                             }
+                             if (lines > 1) {
+                                 output(1000 + lines);
+                             } else {
+                                 output(64); output(46); output(46); output(10);
+                                 output(35); output(46); output(35); output(10);
+                                 output(46); output(46); output(46); output(10);
+                                 output(35); output(64); output(35); output(10);
+                             }
+                         }";
+
+    fn hulls(lines: &[&str]) -> Vec<Vec<bool>> {
+        lines.iter().map(|line| parse_hull(line)).collect()
+    }
+
+    #[test]
+    fn test_one() {
+        let script = Script::parse("NOT A J\nNOT C T\nAND D T\nOR T J", Mode::Walk).unwrap();
+        assert_eq!(
+            script.to_string(),
+            "NOT A J\nNOT C T\nAND D T\nOR T J\nWALK\n"
+        );
+        assert!(Script::parse("NOT E J", Mode::Walk).is_err());
+        assert!(Script::parse("NOT E J", Mode::Run).is_ok());
+        assert!(Script::parse("NOT A B", Mode::Run).is_err());
+        assert!(Script::parse(&"NOT A J\n".repeat(16), Mode::Walk).is_err());
+
+        let known = hulls(&[
+            "#####.###########",
+            "#####..#.########",
+            "#####...#########",
+        ]);
+        assert!(failures(&script, &known).is_empty());
+        let naive = Script::parse("NOT A J", Mode::Walk).unwrap();
+        assert_eq!(failures(&naive, &known), vec![&known[1][..]]);
+
+        let droid = IntCodeProgram::from_memory(compile(DROID).unwrap());
+        assert_eq!(run_droid(&droid, &script), Ok(Run::Survived(1005)));
+        let empty = Script::parse("", Mode::Run).unwrap();
+        let animation = match run_droid(&droid, &empty) {
+            Ok(Run::Fell(animation)) => animation,
+            run => panic!("Expected the droid to fall: {:?}", run),
+        };
+        assert_eq!(death_hull(&animation), Some(parse_hull("#.#")));
+
+        let broken = IntCodeProgram::from_input("3,0,98");
+        assert!(matches!(
+            run_droid(&broken, &empty),
+            Err(IntCodeStatus::Error(_))
+        ));
+        let waiting = IntCodeProgram::from_input("3,5,1105,1,0,0");
+        assert_eq!(
+            solve(&waiting, empty.clone(), &mut Vec::new()),
+            Err(IntCodeStatus::AwaitingInput)
+        );
+
+        let mut learned = Vec::new();
+        let (script, damage) = solve(&droid, empty, &mut learned).unwrap().unwrap();
+        assert_eq!(learned, hulls(&["#.#"]));
+        assert_eq!(script.to_string(), "NOT A J\nRUN\n");
+        assert_eq!(damage, 1002);
+
+        for (mode, source) in SCRIPTS.iter() {
+            let script = Script::parse(source, *mode).unwrap();
+            assert!(failures(&script, &known).is_empty());
+        }
+    }
+
+    #[test]
+    fn test_two() {
+        let walk = hulls(&[
+            "#####.###########",
+            "#####..#.########",
+            "#####...#########",
+        ]);
+        let script = synthesize(&walk, Mode::Walk).unwrap();
+        assert!(failures(&script, &walk).is_empty());
+        assert!(script.instructions.len() <= 4);
+
+        let run = hulls(&[
+            "#####.###########",
+            "#####...#########",
+            "#####.#.##.#.####",
+            "#####.##.##..####",
+            "#####..#.########",
+            "#####.#.#...#.###",
+        ]);
+        let script = synthesize(&run, Mode::Run).unwrap();
+        assert!(failures(&script, &run).is_empty());
+        assert!(script.instructions.len() <= MAX_INSTRUCTIONS);
+
+        assert_eq!(synthesize(&hulls(&["#....#"]), Mode::Walk), None);
+        assert_eq!(synthesize(&[], Mode::Walk).unwrap().instructions, vec![]);
+    }
 }
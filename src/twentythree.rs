@@ -1,3 +1,274 @@
-pub fn start(input: &str) {
-    println!("{}", input);
+use crate::intcode::{IntCodeProgram, IntCodeStatus};
+use std::collections::VecDeque;
+use std::fmt;
+
+const NICS: usize = 50;
+const NAT: i64 = 255;
+// Gives up if the NAT hasn't repeated itself after this many rounds.
+const MAX_ROUNDS: usize = 100_000;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+struct Packet {
+    from: Option<usize>,
+    to: i64,
+    x: i64,
+    y: i64,
+}
+
+impl fmt::Display for Packet {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.from {
+            Some(from) => write!(f, "{:>3}", from)?,
+            None => write!(f, "NAT")?,
+        }
+        write!(f, " -> {:>3}: X={} Y={}", self.to, self.x, self.y)
+    }
+}
+
+struct Network {
+    nics: Vec<IntCodeProgram>,
+    queues: Vec<VecDeque<(i64, i64)>>,
+    // The last packet sent to the NAT, waiting for the network to go idle.
+    nat: Option<(i64, i64)>,
+    verbose: bool,
+}
+
+impl Network {
+    fn boot(program: &IntCodeProgram, size: usize, verbose: bool) -> Network {
+        let nics = (0..size)
+            .map(|address| {
+                let mut nic = program.clone();
+                nic.in_buf.push(address as i64);
+                nic
+            })
+            .collect();
+        Network {
+            nics,
+            queues: vec![VecDeque::new(); size],
+            nat: None,
+            verbose,
+        }
+    }
+
+    // Gives every NIC its queued packets, or -1 if there are none, and runs
+    // it until it wants more input. Returns the packets sent meanwhile, or
+    // the address and status of a NIC that halted or failed instead.
+    fn round(&mut self) -> Result<Vec<Packet>, (usize, IntCodeStatus)> {
+        let mut sent = Vec::new();
+        for (address, nic) in self.nics.iter_mut().enumerate() {
+            if self.queues[address].is_empty() {
+                nic.in_buf.push(-1);
+            }
+            for (x, y) in self.queues[address].drain(..) {
+                nic.in_buf.push(x);
+                nic.in_buf.push(y);
+            }
+            nic.run();
+            if nic.status != IntCodeStatus::AwaitingInput {
+                return Err((address, nic.status.clone()));
+            }
+
+            let complete = nic.out_buf.len() / 3 * 3;
+            for triple in nic.out_buf.drain(..complete).collect::<Vec<_>>().chunks(3) {
+                sent.push(Packet {
+                    from: Some(address),
+                    to: triple[0],
+                    x: triple[1],
+                    y: triple[2],
+                });
+            }
+        }
+        Ok(sent)
+    }
+
+    fn route(&mut self, packet: Packet) {
+        if self.verbose {
+            println!("{}", packet);
+        }
+        match packet.to {
+            NAT => self.nat = Some((packet.x, packet.y)),
+            to if (0..self.queues.len() as i64).contains(&to) => {
+                self.queues[to as usize].push_back((packet.x, packet.y))
+            }
+            to => {
+                if self.verbose {
+                    println!("Dropped packet for unknown address {}", to);
+                }
+            }
+        }
+    }
+
+    fn is_idle(&self) -> bool {
+        self.queues.iter().all(VecDeque::is_empty)
+    }
+}
+
+pub fn start(input: &str, args: &[String]) {
+    let verbose = args.iter().any(|arg| arg == "-v" || arg == "--verbose");
+    let program = IntCodeProgram::from_input(input);
+
+    let (first, repeated) = match run_network(&program, NICS, verbose) {
+        Ok(results) => results,
+        Err((address, status)) => return println!("NIC {} stopped: {:?}", address, status),
+    };
+    match first {
+        Some(y) => println!("First Y sent to 255: {}", y),
+        None => println!("Nothing was sent to 255"),
+    }
+    match repeated {
+        Some(y) => println!("First Y delivered twice by the NAT: {}", y),
+        None => println!("The NAT never repeated itself"),
+    }
+}
+
+// Returns the first Y value sent to the NAT, and the first Y value the NAT
+// sends to address 0 twice in a row.
+fn run_network(
+    program: &IntCodeProgram,
+    size: usize,
+    verbose: bool,
+) -> Result<(Option<i64>, Option<i64>), (usize, IntCodeStatus)> {
+    let mut network = Network::boot(program, size, verbose);
+    let mut first = None;
+    let mut last_delivered = None;
+
+    for _ in 0..MAX_ROUNDS {
+        // The network is idle if nothing was waiting to be received at the
+        // start of the round and nothing was sent during it.
+        let idle_before = network.is_idle();
+        let sent = network.round()?;
+        let idle = idle_before && sent.is_empty();
+        for packet in sent {
+            network.route(packet);
+            if packet.to == NAT && first.is_none() {
+                first = Some(packet.y);
+            }
+        }
+        if !idle {
+            continue;
+        }
+
+        let (x, y) = match network.nat {
+            Some(packet) => packet,
+            None => return Ok((first, None)),
+        };
+        if verbose {
+            println!("Network idle");
+        }
+        if last_delivered == Some(y) {
+            return Ok((first, Some(y)));
+        }
+        last_delivered = Some(y);
+        network.route(Packet {
+            from: None,
+            to: 0,
+            x,
+            y,
+        });
+    }
+    Ok((first, None))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::intcode::compiler::compile;
+
+    // NIC 0 starts a packet along the chain of addresses. Each NIC passes it
+    // on to the next, and the one at LAST sends it to the NAT, taking 3 off Y
+    // while it's over 5.
+    const NIC: &str = "fn main() {
+                           let address = input();
+                           let x = 0;
+                           let y = 0;
+                           if (address == 0) { output(1); output(0); output(20); }
+                           while (1) {
+                               x = input();
+                               if (x != 0 - 1) {
+                                   y = input();
+                                   if (address == LAST) {
+                                       if (y > 5) { y = y - 3; }
+                                       output(255);
+                                   } else {
+                                       output(address + 1);
+                                   }
+                                   output(x + 1);
+                                   output(y);
+                               }
+                           }
+                       }";
+
+    fn nic(size: usize) -> IntCodeProgram {
+        let source = NIC.replace("LAST", &(size - 1).to_string());
+        IntCodeProgram::from_memory(compile(&source).unwrap())
+    }
+
+    #[test]
+    fn test_one() {
+        let program = nic(NICS);
+        let (first, _) = run_network(&program, NICS, false).unwrap();
+        assert_eq!(first, Some(17));
+
+        let packet = Packet {
+            from: None,
+            to: 0,
+            x: 98,
+            y: 5,
+        };
+        assert_eq!(packet.to_string(), "NAT ->   0: X=98 Y=5");
+    }
+
+    // NIC 0 sends Y=7 to the NAT and a packet to NIC 1, which takes two more
+    // rounds to send Y=5 to the NAT. Nobody answers the NAT.
+    const SLOW_NIC: &str = "fn main() {
+                                let address = input();
+                                let x = 0;
+                                let wait = 0;
+                                if (address == 0) {
+                                    output(255); output(0); output(7);
+                                    output(1); output(0); output(0);
+                                }
+                                while (1) {
+                                    x = input();
+                                    if (x == 0 - 1) {
+                                        if (wait > 0) {
+                                            wait = wait - 1;
+                                            if (wait == 0) { output(255); output(0); output(5); }
+                                        }
+                                    } else {
+                                        x = input();
+                                        if (address == 1) { wait = 2; }
+                                    }
+                                }
+                            }";
+
+    #[test]
+    fn test_two() {
+        assert_eq!(
+            run_network(&nic(NICS), NICS, false),
+            Ok((Some(17), Some(5)))
+        );
+        assert_eq!(run_network(&nic(3), 3, false), Ok((Some(17), Some(5))));
+
+        // Without the last NIC nothing reaches the NAT, so it never wakes
+        // the network up again.
+        assert_eq!(run_network(&nic(NICS + 1), NICS, false), Ok((None, None)));
+
+        // Rounds where packets are received but nothing is sent aren't idle,
+        // so the NAT doesn't send Y=7 twice before NIC 1 gets to send Y=5.
+        let program = IntCodeProgram::from_memory(compile(SLOW_NIC).unwrap());
+        assert_eq!(run_network(&program, 2, false), Ok((Some(7), Some(5))));
+
+        // A NIC that halts or faults stops the network.
+        let halting = IntCodeProgram::from_input("3,0,99");
+        assert_eq!(
+            run_network(&halting, 2, false),
+            Err((0, IntCodeStatus::Halted))
+        );
+        let broken = IntCodeProgram::from_input("3,0,98");
+        assert!(matches!(
+            run_network(&broken, 2, false),
+            Err((0, IntCodeStatus::Error(_)))
+        ));
+    }
 }
@@ -1,3 +1,231 @@
+const SMALL_DECK: u128 = 10007;
+const LARGE_DECK: u128 = 119_315_717_514_047;
+const REPETITIONS: u128 = 101_741_582_076_661;
+
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Technique {
+    NewStack,
+    Cut(i64),
+    Increment(u64),
+}
+
+fn parse(input: &str) -> Result<Vec<Technique>, String> {
+    input
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(|line| {
+            let arg = line.rsplit(' ').next().unwrap_or("");
+            if line == "deal into new stack" {
+                Ok(Technique::NewStack)
+            } else if line.starts_with("cut ") {
+                arg.parse()
+                    .map(Technique::Cut)
+                    .map_err(|_| line.to_string())
+            } else if line.starts_with("deal with increment ") {
+                arg.parse()
+                    .map(Technique::Increment)
+                    .map_err(|_| line.to_string())
+            } else {
+                Err(line.to_string())
+            }
+        })
+        .collect::<Result<_, _>>()
+        .map_err(|line| format!("Unknown technique: {}", line))
+}
+
+// Where a card at position `x` ends up: `a * x + b`, modulo the deck size.
+// Sizes must stay below 2^63 so products fit in a u128.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+struct Linear {
+    a: u128,
+    b: u128,
+    m: u128,
+}
+
+impl Linear {
+    fn identity(m: u128) -> Linear {
+        Linear { a: 1, b: 0, m }
+    }
+
+    fn from_technique(technique: Technique, m: u128) -> Linear {
+        let (a, b) = match technique {
+            Technique::NewStack => (m - 1, m - 1),
+            Technique::Cut(n) => (1, (m as i128 - n as i128).rem_euclid(m as i128) as u128),
+            Technique::Increment(n) => (n as u128 % m, 0),
+        };
+        Linear { a, b, m }
+    }
+
+    fn from_shuffle(shuffle: &[Technique], m: u128) -> Linear {
+        shuffle.iter().fold(Linear::identity(m), |acc, t| {
+            acc.then(Linear::from_technique(*t, m))
+        })
+    }
+
+    fn apply(&self, x: u128) -> u128 {
+        (self.a * x + self.b) % self.m
+    }
+
+    // This transform followed by `next`.
+    fn then(&self, next: Linear) -> Linear {
+        Linear {
+            a: self.a * next.a % self.m,
+            b: (self.b * next.a + next.b) % self.m,
+            m: self.m,
+        }
+    }
+
+    // Applying the transform `n` times, by repeated squaring.
+    fn pow(&self, mut n: u128) -> Linear {
+        let mut result = Linear::identity(self.m);
+        let mut square = *self;
+        while n > 0 {
+            if n & 1 == 1 {
+                result = result.then(square);
+            }
+            square = square.then(square);
+            n >>= 1;
+        }
+        result
+    }
+
+    // Maps positions back to the cards that end up there. Only exists when
+    // `a` is coprime to the deck size, which it is for any valid shuffle.
+    fn inverse(&self) -> Option<Linear> {
+        let a = mod_inverse(self.a, self.m)?;
+        Some(Linear {
+            a,
+            b: (self.m - self.b) % self.m * a % self.m,
+            m: self.m,
+        })
+    }
+}
+
+// Extended Euclid, which unlike Fermat's little theorem also works for decks
+// whose size isn't prime.
+fn mod_inverse(a: u128, m: u128) -> Option<u128> {
+    let (mut r0, mut r1) = (m as i128, (a % m) as i128);
+    let (mut t0, mut t1) = (0i128, 1i128);
+    while r1 != 0 {
+        let q = r0 / r1;
+        (r0, r1) = (r1, r0 - q * r1);
+        (t0, t1) = (t1, t0 - q * t1);
+    }
+    if r0 != 1 {
+        return None;
+    }
+    Some(t0.rem_euclid(m as i128) as u128)
+}
+
 pub fn start(input: &str) {
-    println!("{}", input);
+    let shuffle = match parse(input) {
+        Ok(shuffle) => shuffle,
+        Err(e) => return println!("{}", e),
+    };
+
+    let small = Linear::from_shuffle(&shuffle, SMALL_DECK);
+    println!("Position of card 2019: {}", small.apply(2019));
+
+    let large = Linear::from_shuffle(&shuffle, LARGE_DECK).pow(REPETITIONS);
+    match large.inverse() {
+        Some(inverse) => println!("Card at position 2020: {}", inverse.apply(2020)),
+        None => println!("The shuffle can't be undone"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Shuffles a real deck, one technique at a time.
+    fn deal(shuffle: &[Technique], size: usize) -> Vec<u128> {
+        let mut deck: Vec<u128> = (0..size as u128).collect();
+        for technique in shuffle.iter() {
+            match technique {
+                Technique::NewStack => deck.reverse(),
+                Technique::Cut(n) => {
+                    let n = n.rem_euclid(size as i64) as usize;
+                    deck.rotate_left(n);
+                }
+                Technique::Increment(n) => {
+                    let mut dealt = deck.clone();
+                    for (i, card) in deck.iter().enumerate() {
+                        dealt[i * *n as usize % size] = *card;
+                    }
+                    deck = dealt;
+                }
+            }
+        }
+        deck
+    }
+
+    #[test]
+    fn test_one() {
+        let examples = [
+            (
+                "deal with increment 7\ndeal into new stack\ndeal into new stack",
+                [0, 3, 6, 9, 2, 5, 8, 1, 4, 7],
+            ),
+            (
+                "cut 6\ndeal with increment 7\ndeal into new stack",
+                [3, 0, 7, 4, 1, 8, 5, 2, 9, 6],
+            ),
+            (
+                "deal with increment 7\ndeal with increment 9\ncut -2",
+                [6, 3, 0, 7, 4, 1, 8, 5, 2, 9],
+            ),
+            (
+                "deal into new stack\ncut -2\ndeal with increment 7\ncut 8\ncut -4\n\
+                 deal with increment 7\ncut 3\ndeal with increment 9\n\
+                 deal with increment 3\ncut -1",
+                [9, 2, 5, 8, 1, 4, 7, 0, 3, 6],
+            ),
+        ];
+        for (input, expected) in examples.iter() {
+            let shuffle = parse(input).unwrap();
+            assert_eq!(deal(&shuffle, 10), expected);
+
+            let position = Linear::from_shuffle(&shuffle, 10);
+            for (i, card) in expected.iter().enumerate() {
+                assert_eq!(position.apply(*card), i as u128);
+            }
+            let inverse = position.inverse().unwrap();
+            let deck: Vec<u128> = (0..10).map(|i| inverse.apply(i)).collect();
+            assert_eq!(deck, expected);
+        }
+
+        assert!(parse("deal with increment x").is_err());
+        assert!(parse("shuffle").is_err());
+        assert_eq!(
+            Linear::from_technique(Technique::Increment(2), 10).inverse(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_two() {
+        let shuffle = parse(
+            "deal with increment 64\ncut 2419\ndeal into new stack\n\
+             cut -7734\ndeal with increment 13\ncut 1001",
+        )
+        .unwrap();
+
+        let mut deck = (0..SMALL_DECK).collect::<Vec<_>>();
+        for _ in 0..50 {
+            let once = deal(&shuffle, SMALL_DECK as usize);
+            deck = once.iter().map(|i| deck[*i as usize]).collect();
+        }
+
+        let repeated = Linear::from_shuffle(&shuffle, SMALL_DECK).pow(50);
+        let inverse = repeated.inverse().unwrap();
+        for position in [0, 1, 2019, 2020, 10006].iter() {
+            assert_eq!(inverse.apply(*position), deck[*position as usize]);
+            assert_eq!(repeated.apply(deck[*position as usize]), *position);
+        }
+
+        let large = Linear::from_shuffle(&shuffle, LARGE_DECK).pow(REPETITIONS);
+        let inverse = large.inverse().unwrap();
+        assert_eq!(large.apply(inverse.apply(2020)), 2020);
+    }
 }
@@ -1,20 +1,67 @@
+use crate::intcode::opcodes::OpcodeTable;
+use crate::intcode::search::{Patch, Search};
+use crate::intcode::symbolic::SymbolicProgram;
 use crate::intcode::IntCodeProgram;
 
+const TARGET: i64 = 19690720;
+
 pub fn start(input: &str) {
-    let program = IntCodeProgram::from_input(&input);
-    println!("{:?}", program);
+    let mut program = IntCodeProgram::from_input(input);
+    program.set_opcodes(OpcodeTable::introduced_by(2));
+
+    match solve_symbolic(&program).or_else(|| solve_search(&program)) {
+        Some((noun, verb)) => println!("{}", 100 * noun + verb),
+        None => println!("No noun and verb produce {}", TARGET),
+    }
+}
 
+fn solve_symbolic(program: &IntCodeProgram) -> Option<(i64, i64)> {
+    let mut symbolic = SymbolicProgram::new(program, &[1, 2]);
+    symbolic.run().ok()?;
+    let output = symbolic.get(0).linear()?;
+
+    let (noun_coeff, verb_coeff) = (output.coeff(1), output.coeff(2));
     for noun in 0..100 {
-        for verb in 0..100 {
-            let mut cp = program.clone();
-            cp.set_at(1, noun);
-            cp.set_at(2, verb);
-            cp.run();
-
-            if cp.get(0) == 19690720 {
-                println!("{}", 100 * noun + verb);
-                break;
-            }
+        let rem = match TARGET
+            .checked_sub(output.constant)
+            .and_then(|rem| rem.checked_sub(noun_coeff.checked_mul(noun)?))
+        {
+            Some(rem) => rem,
+            None => continue,
+        };
+        let verb = match verb_coeff {
+            0 if rem == 0 => 0,
+            0 => continue,
+            _ if rem % verb_coeff != 0 => continue,
+            _ => rem / verb_coeff,
+        };
+        if (0..100).contains(&verb) {
+            return Some((noun, verb));
         }
     }
+    None
+}
+
+fn solve_search(program: &IntCodeProgram) -> Option<(i64, i64)> {
+    let candidates =
+        (0..100).flat_map(|noun| (0..100).map(move |verb| Patch(vec![(1, noun), (2, verb)])));
+
+    Search::new(program)
+        .first(candidates, |p| p.get(0) == TARGET)
+        .map(|Patch(cells)| (cells[0].1, cells[1].1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_one() {
+        let input = "1,0,0,3,2,1,21,3,1,3,2,3,1,3,22,0,99,0,0,0,0,600000,490702";
+        let program = IntCodeProgram::from_input(input);
+        let noun_verb = (32, 18);
+
+        assert_eq!(solve_symbolic(&program), Some(noun_verb));
+        assert_eq!(solve_search(&program), Some(noun_verb));
+    }
 }
@@ -0,0 +1,375 @@
+// Property tests for the Intcode interpreter over randomly generated programs.
+//
+// Set INTCODE_FUZZ_CASES to run more programs and INTCODE_FUZZ_SEED to pick a
+// different sequence; failures report the seed and case needed to reproduce.
+
+#![cfg(feature = "std")]
+
+use aoc19::intcode::{IntCodeError, IntCodeProgram, IntCodeStatus};
+use std::collections::{BTreeSet, VecDeque};
+use std::env;
+
+const BUDGET: u64 = 2_000;
+
+struct Rng(u64);
+
+impl Rng {
+    fn new(seed: u64) -> Rng {
+        Rng(seed.max(1))
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, n: u64) -> u64 {
+        self.next() % n
+    }
+
+    fn range(&mut self, lo: i64, hi: i64) -> i64 {
+        lo + self.below((hi - lo) as u64) as i64
+    }
+
+    fn chance(&mut self, one_in: u64) -> bool {
+        self.below(one_in) == 0
+    }
+}
+
+fn setting(name: &str, default: u64) -> u64 {
+    env::var(name)
+        .ok()
+        .and_then(|v| v.parse().ok())
+        .unwrap_or(default)
+}
+
+// Yields `(seed, case, program, inputs)` for every generated case.
+fn cases() -> impl Iterator<Item = (u64, u64, Vec<i64>, Vec<i64>)> {
+    let seed = setting("INTCODE_FUZZ_SEED", 0x5eed_1c0de);
+    let count = setting("INTCODE_FUZZ_CASES", 300);
+    let mut rng = Rng::new(seed);
+    (0..count).map(move |case| {
+        let program = generate(&mut rng);
+        let inputs = (0..rng.below(4)).map(|_| rng.range(-100, 100)).collect();
+        (seed, case, program, inputs)
+    })
+}
+
+// Mostly well-formed programs: real opcodes with in-range parameters, jumps
+// to instruction starts and a little data after the code. Now and then a
+// mode or opcode is corrupted so the error paths get exercised too.
+fn generate(rng: &mut Rng) -> Vec<i64> {
+    const OPCODES: [(i64, usize); 10] = [
+        (1, 3),
+        (2, 3),
+        (3, 1),
+        (4, 1),
+        (5, 2),
+        (6, 2),
+        (7, 3),
+        (8, 3),
+        (9, 1),
+        (99, 0),
+    ];
+
+    let count = rng.range(3, 30) as usize;
+    let ops: Vec<(i64, usize)> = (0..count)
+        .map(|_| OPCODES[rng.below(OPCODES.len() as u64) as usize])
+        .collect();
+    let mut starts = Vec::new();
+    let mut len = 0;
+    for (_, params) in ops.iter() {
+        starts.push(len as i64);
+        len += params + 1;
+    }
+    let data = rng.range(1, 10) as usize;
+    let total = (len + data) as i64;
+
+    let mut memory = Vec::new();
+    for (opcode, params) in ops.iter() {
+        let mut modes = Vec::new();
+        let mut values = Vec::new();
+        for i in 0..*params {
+            let jump_target = (*opcode == 5 || *opcode == 6) && i == 1;
+            let mode = match rng.below(3) {
+                _ if jump_target && !rng.chance(4) => 1,
+                _ if rng.chance(60) => rng.range(3, 10),
+                mode => mode as i64,
+            };
+            let value = match mode {
+                1 if jump_target => starts[rng.below(starts.len() as u64) as usize],
+                1 if *opcode == 9 => rng.range(-3, 6),
+                1 => rng.range(-50, 50),
+                2 => rng.range(-4, total),
+                _ if rng.chance(40) => rng.range(-5, 0),
+                _ => rng.range(0, total),
+            };
+            modes.push(mode);
+            values.push(value);
+        }
+
+        let mut instr = *opcode;
+        for (i, mode) in modes.iter().enumerate() {
+            instr += mode * 10i64.pow(i as u32 + 2);
+        }
+        if rng.chance(80) {
+            instr = rng.range(10, 98);
+        }
+        memory.push(instr);
+        memory.extend(values);
+    }
+    memory.extend((0..data).map(|_| rng.range(-1000, 1000)));
+    memory
+}
+
+// A deliberately naive interpreter written straight from the puzzle text,
+// mirroring the VM's error model.
+struct Reference {
+    memory: Vec<i64>,
+    pointer: usize,
+    rel_base: i64,
+    steps: u64,
+    inputs: VecDeque<i64>,
+    outputs: Vec<i64>,
+    seen: BTreeSet<(i64, i64)>,
+}
+
+impl Reference {
+    fn new(program: &[i64], inputs: &[i64]) -> Reference {
+        let mut memory = program.to_vec();
+        memory.resize(program.len() * 128, 0);
+        Reference {
+            memory,
+            pointer: 0,
+            rel_base: 0,
+            steps: 0,
+            inputs: inputs.iter().cloned().collect(),
+            outputs: Vec::new(),
+            seen: BTreeSet::new(),
+        }
+    }
+
+    fn run(&mut self, budget: u64) -> IntCodeStatus {
+        loop {
+            if self.steps >= budget {
+                return IntCodeStatus::BudgetExhausted;
+            }
+            match self.step() {
+                Ok(None) => self.steps += 1,
+                Ok(Some(IntCodeStatus::Halted)) => {
+                    self.steps += 1;
+                    return IntCodeStatus::Halted;
+                }
+                Ok(Some(status)) => return status,
+                Err(err) => return IntCodeStatus::Error(err),
+            }
+        }
+    }
+
+    fn address(&self, offset: usize, mode: i64) -> Result<usize, IntCodeError> {
+        let idx = self.pointer + offset;
+        let raw = || {
+            self.memory
+                .get(idx)
+                .cloned()
+                .ok_or(IntCodeError::InvalidAddress {
+                    address: idx as i64,
+                    pointer: self.pointer,
+                })
+        };
+        let address = match mode {
+            0 => raw()?,
+            1 => idx as i64,
+            2 => self.rel_base.wrapping_add(raw()?),
+            _ => {
+                return Err(IntCodeError::UnknownMode {
+                    mode,
+                    pointer: self.pointer,
+                })
+            }
+        };
+        if address < 0 || address as usize >= self.memory.len() {
+            return Err(IntCodeError::InvalidAddress {
+                address,
+                pointer: self.pointer,
+            });
+        }
+        Ok(address as usize)
+    }
+
+    fn step(&mut self) -> Result<Option<IntCodeStatus>, IntCodeError> {
+        let pointer = self.pointer;
+        let instr = match self.memory.get(pointer) {
+            Some(instr) => *instr,
+            None => {
+                return Err(IntCodeError::InvalidAddress {
+                    address: pointer as i64,
+                    pointer,
+                })
+            }
+        };
+        let opcode = instr % 100;
+        let params = match opcode {
+            1 | 2 | 7 | 8 => 3,
+            5 | 6 => 2,
+            3 | 4 | 9 => 1,
+            99 => 0,
+            _ => return Err(IntCodeError::UnknownOpcode { opcode, pointer }),
+        };
+
+        let mut at = [0; 3];
+        let mut modes = instr / 100;
+        for (i, address) in at.iter_mut().take(params).enumerate() {
+            *address = self.address(i + 1, modes % 10)?;
+            self.seen.insert((opcode, modes % 10));
+            modes /= 10;
+        }
+        if params == 0 {
+            self.seen.insert((opcode, 0));
+        }
+
+        let a = self.memory[at[0]];
+        let b = self.memory[at[1]];
+        let mut next = pointer + params + 1;
+        match opcode {
+            1 => self.memory[at[2]] = a.wrapping_add(b),
+            2 => self.memory[at[2]] = a.wrapping_mul(b),
+            3 => match self.inputs.pop_front() {
+                Some(value) => self.memory[at[0]] = value,
+                None => return Ok(Some(IntCodeStatus::AwaitingInput)),
+            },
+            4 => self.outputs.push(a),
+            5 if a != 0 => next = b as usize,
+            6 if a == 0 => next = b as usize,
+            5 | 6 => {}
+            7 => self.memory[at[2]] = (a < b) as i64,
+            8 => self.memory[at[2]] = (a == b) as i64,
+            9 => self.rel_base = self.rel_base.wrapping_add(a),
+            _ => return Ok(Some(IntCodeStatus::Halted)),
+        }
+        self.pointer = next;
+        Ok(None)
+    }
+}
+
+fn start(program: &[i64], inputs: &[i64], budget: u64) -> IntCodeProgram {
+    let mut vm = IntCodeProgram::from_memory(program.to_vec());
+    vm.in_buf = inputs.to_vec();
+    vm.set_budget(Some(budget));
+    vm
+}
+
+#[test]
+fn test_matches_reference() {
+    let mut seen = BTreeSet::new();
+    let mut statuses = BTreeSet::new();
+
+    for (seed, case, program, inputs) in cases() {
+        let mut vm = start(&program, &inputs, BUDGET);
+        vm.run();
+        let mut reference = Reference::new(&program, &inputs);
+        let status = reference.run(BUDGET);
+
+        let context = format!("seed {} case {}: {:?}", seed, case, program);
+        assert_eq!(vm.status, status, "{}", context);
+        assert_eq!(vm.out_buf, reference.outputs, "{}", context);
+        assert_eq!(vm.pointer(), reference.pointer, "{}", context);
+        assert_eq!(vm.rel_base(), reference.rel_base, "{}", context);
+        assert_eq!(vm.steps(), reference.steps, "{}", context);
+        assert_eq!(vm.snapshot().memory, reference.memory, "{}", context);
+
+        seen.extend(reference.seen);
+        statuses.insert(match status {
+            IntCodeStatus::Error(IntCodeError::UnknownOpcode { .. }) => "unknown opcode",
+            IntCodeStatus::Error(IntCodeError::UnknownMode { .. }) => "unknown mode",
+            IntCodeStatus::Error(IntCodeError::InvalidAddress { .. }) => "invalid address",
+            IntCodeStatus::AwaitingInput => "awaiting input",
+            IntCodeStatus::BudgetExhausted => "budget exhausted",
+            IntCodeStatus::Halted => "halted",
+            _ => "other",
+        });
+    }
+
+    for opcode in 1..=9 {
+        for mode in 0..=2 {
+            assert!(
+                seen.contains(&(opcode, mode)),
+                "{} in mode {}",
+                opcode,
+                mode
+            );
+        }
+    }
+    assert!(seen.contains(&(99, 0)));
+    assert_eq!(statuses.len(), 6, "{:?}", statuses);
+}
+
+#[test]
+fn test_clones_are_deterministic() {
+    for (seed, case, program, inputs) in cases() {
+        let mut vm = start(&program, &inputs, BUDGET / 2);
+        vm.run();
+
+        let mut copy = vm.clone();
+        vm.set_budget(Some(BUDGET));
+        copy.set_budget(Some(BUDGET));
+        vm.run();
+        copy.run();
+        assert_eq!(
+            vm.snapshot(),
+            copy.snapshot(),
+            "seed {} case {}: {:?}",
+            seed,
+            case,
+            program
+        );
+    }
+}
+
+#[test]
+fn test_snapshot_round_trip() {
+    let mut rng = Rng::new(setting("INTCODE_FUZZ_SEED", 0x5eed_1c0de));
+    for (seed, case, program, inputs) in cases() {
+        let context = format!("seed {} case {}: {:?}", seed, case, program);
+        let mut vm = start(&program, &inputs, rng.below(BUDGET));
+        vm.run();
+        let midway = vm.snapshot();
+
+        vm.set_budget(Some(BUDGET));
+        vm.run();
+        let finished = vm.snapshot();
+
+        vm.restore(&midway);
+        assert_eq!(vm.snapshot(), midway, "{}", context);
+        vm.run();
+        assert_eq!(vm.snapshot(), finished, "{}", context);
+
+        let mut fresh = start(&[0], &[], BUDGET);
+        fresh.restore(&midway);
+        fresh.run();
+        assert_eq!(fresh.snapshot(), finished, "{}", context);
+    }
+}
+
+#[test]
+fn test_random_memory_never_panics() {
+    let count = setting("INTCODE_FUZZ_CASES", 300);
+    let mut rng = Rng::new(setting("INTCODE_FUZZ_SEED", 0x5eed_1c0de));
+    for _ in 0..count {
+        let len = rng.range(1, 64) as usize;
+        let memory = (0..len)
+            .map(|_| match rng.below(4) {
+                0 => rng.next() as i64,
+                1 => rng.range(-10, 10),
+                _ => rng.range(0, 22300),
+            })
+            .collect();
+        let mut vm = IntCodeProgram::from_memory(memory);
+        vm.in_buf = (0..8).map(|_| rng.next() as i64).collect();
+        vm.set_budget(Some(BUDGET));
+        vm.run();
+        assert!(vm.status != IntCodeStatus::Ready);
+    }
+}
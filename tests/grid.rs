@@ -0,0 +1,61 @@
+use aoc19::grid::{Direction, Grid};
+use aoc19::pathfinding::{bfs, dijkstra};
+
+#[test]
+fn test_parse() {
+    let grid = Grid::parse("#####\n#.@.#\n  #\n\n");
+
+    assert_eq!((grid.width(), grid.height()), (5, 3));
+    assert_eq!(grid.find(b'@'), Some((2, 1)));
+    assert_eq!(grid.get((0, 2)), Some(b' '));
+    assert_eq!(grid.get((4, 2)), Some(b' '));
+    assert_eq!(grid.get((5, 0)), None);
+    assert_eq!(grid.to_string(), "#####\n#.@.#\n  #  \n");
+}
+
+#[test]
+fn test_neighbours() {
+    let mut grid = Grid::new(3, 2, b'.');
+    grid.set((1, 1), b'#');
+
+    let around: Vec<_> = grid.neighbours((0, 0)).collect();
+    assert_eq!(around, vec![(1, 0), (0, 1)]);
+    assert_eq!(grid.positions(|c| c == b'.').count(), 5);
+    assert_eq!(grid.step((1, 0), Direction::Down), Some((1, 1)));
+    assert_eq!(grid.step((1, 0), Direction::Up), None);
+
+    assert_eq!(Direction::Up.turn_left(), Direction::Left);
+    assert_eq!(Direction::Left.turn_right(), Direction::Up);
+    assert_eq!(Direction::Right.reverse(), Direction::Left);
+}
+
+#[test]
+fn test_bfs() {
+    let grid = Grid::parse("#####\n#..##\n#.#.#\n#...#\n#####");
+    let paths = grid.bfs((1, 1), |c| c != b'#');
+
+    assert_eq!(paths.distance((3, 2)), Some(5));
+    assert_eq!(
+        paths.route((3, 2)),
+        vec![(1, 2), (1, 3), (2, 3), (3, 3), (3, 2)]
+    );
+    assert_eq!(paths.distance((2, 2)), None);
+    assert_eq!(paths.route((2, 2)), vec![]);
+}
+
+#[test]
+fn test_dijkstra() {
+    // Reach 10 from 1 by adding one (cost 1) or doubling (cost 3).
+    let found = dijkstra(1u32, |n| vec![(n + 1, 1), (n * 2, 3)], |n| *n == 10);
+    assert_eq!(found, Some((10, 7)));
+}
+
+#[test]
+fn test_state_bfs() {
+    // Reach 10 from 1 by adding one or doubling, in the fewest moves.
+    let path = bfs(1u32, |n| vec![n + 1, n * 2], |n| *n == 10);
+    assert_eq!(path, Some(vec![1, 2, 4, 5, 10]));
+
+    let doubling = |n: &u32| if *n < 100 { vec![n * 2] } else { vec![] };
+    assert_eq!(bfs(1u32, doubling, |n| *n == 10), None);
+}
@@ -0,0 +1,69 @@
+#![cfg(feature = "std")]
+
+use aoc19::intcode::compiler::compile;
+use aoc19::intcode::loader;
+use aoc19::intcode::search::{Inputs, Search};
+use aoc19::intcode::session::Session;
+use aoc19::intcode::{IntCodeProgram, IntCodeStatus};
+
+const QUINE: &str = "109,1,204,-1,1001,100,1,100,1008,100,16,101,1006,101,0,99";
+
+#[test]
+fn test_quine() {
+    let mut program = IntCodeProgram::from_input(QUINE);
+    program.run();
+
+    assert_eq!(program.status, IntCodeStatus::Halted);
+    assert_eq!(program.out_buf, loader::parse(QUINE).unwrap());
+}
+
+#[test]
+fn test_compiled_program() {
+    let source = "fn main() { let n = input(); output(n * n); }";
+    let mut program = IntCodeProgram::from_memory(compile(source).unwrap());
+    program.run();
+    assert_eq!(program.status, IntCodeStatus::AwaitingInput);
+
+    program.in_buf.push(12);
+    program.run();
+    assert_eq!(program.status, IntCodeStatus::Halted);
+    assert_eq!(program.out_buf, vec![144]);
+}
+
+#[test]
+fn test_search() {
+    // Outputs whether the input equals 8.
+    let base = IntCodeProgram::from_input("3,9,8,9,10,9,4,9,99,-1,8");
+    let found = Search::new(&base)
+        .threads(4)
+        .first((0..20).map(|i| Inputs(vec![i])), |program| {
+            program.out_buf == vec![1]
+        });
+
+    assert_eq!(found.map(|inputs| inputs.0), Some(vec![8]));
+}
+
+#[test]
+fn test_session_replay() {
+    let mut program = IntCodeProgram::from_input("3,0,4,0,3,0,4,0,99");
+    program.record();
+    program.in_buf.extend(vec![4, 7]);
+    program.run();
+
+    let session = program.take_session().unwrap();
+    let session = Session::parse(&session.to_string()).unwrap();
+    assert_eq!(session.inputs(), vec![4, 7]);
+    assert_eq!(session.outputs(), vec![4, 7]);
+
+    let replayed = session.replay(IntCodeProgram::from_input("3,0,4,0,3,0,4,0,99"));
+    assert_eq!(replayed.unwrap().status, IntCodeStatus::Halted);
+}
+
+#[test]
+fn test_binary_round_trip() {
+    let memory = loader::parse(QUINE).unwrap();
+    assert_eq!(
+        loader::from_bytes(&loader::to_binary(&memory)).unwrap(),
+        memory
+    );
+}